@@ -0,0 +1,291 @@
+use anyhttp::{sync::GenericResponseBody, HttpError, HttpExecutor};
+use http::HeaderValue;
+
+/// Executor for [libcurl](https://curl.se/libcurl/), with the
+/// [curl](https://docs.rs/curl) crate.
+///
+/// Every request uses a new `curl::easy::Easy` handle, so connections are
+/// not reused. Request bodies and response bodies are buffered in memory.
+/// Redirects are followed, and the response URI is the final URL.
+#[derive(Clone, Debug, Default)]
+pub struct CurlExecutor {
+    _private: (),
+}
+
+impl CurlExecutor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl HttpExecutor for CurlExecutor {
+    type RequestBody = anyhttp::RequestBody;
+    type ResponseBody = GenericResponseBody;
+    type Output = Result<anyhttp::Response<GenericResponseBody>, HttpError>;
+
+    fn request_body_from_generic(&self, body: anyhttp::RequestBody) -> Self::RequestBody {
+        body
+    }
+
+    fn new_output_error(&self, error: HttpError) -> Self::Output {
+        Err(error)
+    }
+
+    fn generic_request_body_mut<'a>(
+        &self,
+        body: &'a mut Self::RequestBody,
+    ) -> Option<&'a mut anyhttp::RequestBody> {
+        Some(body)
+    }
+
+    #[cfg(feature = "tracing")]
+    fn instrument_output(
+        &self,
+        output: Self::Output,
+        span: anyhttp::trace::RequestSpan,
+    ) -> Self::Output {
+        span.finish(&output);
+        output
+    }
+
+    fn execute(&self, pre: anyhttp::RequestPre<Self::RequestBody>) -> Self::Output {
+        if pre.cancel.as_ref().is_some_and(|c| c.is_cancelled()) {
+            return Err(HttpError::new_cancelled());
+        }
+        if pre.connect_to.is_some() {
+            return Err(HttpError::new_custom(
+                "connect_to is not supported by the curl executor",
+            ));
+        }
+        let timeout = pre.remaining_timeout()?;
+        let connect_timeout = pre.connect_timeout;
+        let cancel = pre.cancel;
+        let req = pre.request;
+        let tap = pre.tap;
+        let decompress = pre.decompress;
+        let is_head = req.method == http::Method::HEAD;
+
+        let body = match req.body {
+            anyhttp::RequestBody::Empty => None,
+            anyhttp::RequestBody::Bytes(bytes) => Some(bytes),
+            anyhttp::RequestBody::Read(mut reader) => {
+                let mut bytes = Vec::new();
+                reader.read_to_end(&mut bytes).map_err(|err| {
+                    HttpError::new_io(err, Some("could not read request body".to_string()))
+                })?;
+                Some(bytes)
+            }
+            anyhttp::RequestBody::Stream(stream) => Some(stream.read_to_end_blocking()?),
+        };
+        // Servers may reject mutating requests without a `Content-Length`.
+        let needs_length = matches!(
+            req.method,
+            http::Method::POST | http::Method::PUT | http::Method::PATCH
+        );
+        let body = body.or_else(|| needs_length.then(Vec::new));
+
+        let mut easy = curl::easy::Easy::new();
+        easy.url(&req.uri.to_string()).map_err(map_error)?;
+        match req.method {
+            http::Method::GET => easy.get(true),
+            http::Method::HEAD => easy.nobody(true),
+            // Unlike a custom `POST`, this allows curl to switch to `GET`
+            // on redirects.
+            http::Method::POST => easy.post(true),
+            ref other => easy.custom_request(other.as_str()),
+        }
+        .map_err(map_error)?;
+        if let Some(body) = &body {
+            easy.post_fields_copy(body).map_err(map_error)?;
+        }
+        easy.follow_location(true).map_err(map_error)?;
+        if let Some(timeout) = timeout {
+            easy.timeout(timeout).map_err(map_error)?;
+        }
+        if let Some(timeout) = connect_timeout {
+            easy.connect_timeout(timeout).map_err(map_error)?;
+        }
+
+        let mut list = curl::easy::List::new();
+        for (key, value) in &req.headers {
+            let value = value.to_str().map_err(|_err| {
+                HttpError::new_custom(format!(
+                    "could not re-parse request header '{key}': non-utf8 value"
+                ))
+            })?;
+            // curl drops headers without a value, unless they end with `;`.
+            let line = if value.is_empty() {
+                format!("{key};")
+            } else {
+                format!("{key}: {value}")
+            };
+            list.append(&line).map_err(map_error)?;
+        }
+        // curl adds a form content type to bodies by default.
+        if body.is_some() && !req.headers.contains_key(http::header::CONTENT_TYPE) {
+            list.append("Content-Type:").map_err(map_error)?;
+        }
+        easy.http_headers(list).map_err(map_error)?;
+
+        if cancel.is_some() {
+            // The progress callback, which checks the token during the
+            // transfer, only runs when this is enabled.
+            easy.progress(true).map_err(map_error)?;
+        }
+
+        let mut head = ResponseHead::default();
+        let mut data = Vec::new();
+        let result = {
+            let mut transfer = easy.transfer();
+            transfer
+                .header_function(|line| {
+                    head.parse_line(line);
+                    true
+                })
+                .map_err(map_error)?;
+            transfer
+                .write_function(|chunk| {
+                    data.extend_from_slice(chunk);
+                    Ok(chunk.len())
+                })
+                .map_err(map_error)?;
+            if let Some(cancel) = &cancel {
+                transfer
+                    .progress_function(|_, _, _, _| !cancel.is_cancelled())
+                    .map_err(map_error)?;
+            }
+            transfer.perform()
+        };
+        if let Err(err) = result {
+            if err.is_aborted_by_callback() && cancel.is_some_and(|c| c.is_cancelled()) {
+                return Err(HttpError::new_cancelled());
+            }
+            return Err(map_error(err));
+        }
+
+        let status = easy.response_code().map_err(map_error)?;
+        let status = http::StatusCode::from_u16(status as u16)
+            .map_err(|err| HttpError::new_http(err.into()))?;
+        // The final URI is informational, so a URI that `http` can't parse,
+        // like an overlong redirect target, doesn't fail the request.
+        let uri = easy
+            .effective_url()
+            .ok()
+            .flatten()
+            .and_then(|url| url.parse::<http::Uri>().ok())
+            .unwrap_or_else(|| req.uri.clone());
+
+        let mut headers = head.headers;
+        let mut extensions = http::Extensions::new();
+        if let Some(reason) = head.reason {
+            extensions.insert(anyhttp::ReasonPhrase(reason));
+        }
+
+        let reader: Box<dyn std::io::Read> = Box::new(std::io::Cursor::new(data));
+        let reader = if decompress && !is_head {
+            anyhttp::compression::decode_reader(&mut headers, reader)
+        } else {
+            reader
+        };
+        let body = GenericResponseBody::Read(reader);
+
+        let mut res = anyhttp::Response {
+            uri: Some(uri),
+            status,
+            version: head.version,
+            headers,
+            extensions,
+            body: (),
+        };
+        if let Some(tap) = tap {
+            anyhttp::run_tap(&tap, &mut res)?;
+        }
+
+        Ok(res.map_body(|_| body))
+    }
+}
+
+/// The head of the last response, collected from the header callback.
+///
+/// curl reports the heads of interim responses and redirects too, so each
+/// status line starts over.
+struct ResponseHead {
+    version: http::Version,
+    reason: Option<String>,
+    headers: http::HeaderMap,
+}
+
+impl Default for ResponseHead {
+    fn default() -> Self {
+        Self {
+            version: http::Version::HTTP_11,
+            reason: None,
+            headers: http::HeaderMap::new(),
+        }
+    }
+}
+
+impl ResponseHead {
+    fn parse_line(&mut self, line: &[u8]) {
+        let line = String::from_utf8_lossy(line);
+        let line = line.trim_end_matches(['\r', '\n']);
+        if let Some(status_line) = line.strip_prefix("HTTP/") {
+            let mut parts = status_line.splitn(3, ' ');
+            self.version = match parts.next() {
+                Some("1.0") => http::Version::HTTP_10,
+                Some("2" | "2.0") => http::Version::HTTP_2,
+                Some("3" | "3.0") => http::Version::HTTP_3,
+                _ => http::Version::HTTP_11,
+            };
+            self.reason = parts
+                .nth(1)
+                .map(str::trim)
+                .filter(|reason| !reason.is_empty())
+                .map(str::to_string);
+            self.headers.clear();
+            return;
+        }
+        // Malformed header lines are skipped.
+        let Some((name, value)) = line.split_once(':') else {
+            return;
+        };
+        let (Ok(name), Ok(value)) = (
+            http::header::HeaderName::from_bytes(name.trim().as_bytes()),
+            HeaderValue::from_str(value.trim()),
+        ) else {
+            return;
+        };
+        self.headers.append(name, value);
+    }
+}
+
+fn map_error(err: curl::Error) -> HttpError {
+    if err.is_operation_timedout() {
+        HttpError::new_timeout(err)
+    } else if err.is_couldnt_connect()
+        || err.is_couldnt_resolve_host()
+        || err.is_couldnt_resolve_proxy()
+    {
+        HttpError::new_connect(err)
+    } else {
+        HttpError::new_custom_with_cause("curl error", err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhttp::Client;
+
+    use super::*;
+
+    #[test]
+    fn test_curl_client() {
+        let exec = CurlExecutor::new();
+
+        // Ensure client works as dynamic.
+        let _dyn = Client::new_dyn_sync(exec.clone());
+
+        anyhttp::test::test_sync_executor(exec.clone());
+        anyhttp::test::test_sync_executor_strict(exec);
+    }
+}
@@ -0,0 +1,74 @@
+//! Per-request connect timeouts for pooled connections.
+//!
+//! The connector of a hyper client only sees the URI, so the connect timeout
+//! of a request is provided in a task-local while its response future is
+//! polled. hyper starts opening a new connection during that poll.
+
+use std::{
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures_util::future::BoxFuture;
+use hyper::{service::Service, Uri};
+
+tokio::task_local! {
+    static CONNECT_TIMEOUT: Option<Duration>;
+}
+
+/// Run `f` with the connect timeout of the request that is polled in it.
+pub(crate) fn with_connect_timeout<R>(timeout: Option<Duration>, f: impl FnOnce() -> R) -> R {
+    CONNECT_TIMEOUT.sync_scope(timeout, f)
+}
+
+pub(crate) fn connect_timeout_error() -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::TimedOut,
+        "no connection within the connect timeout",
+    )
+}
+
+/// Connector that applies [`anyhttp::RequestPre::connect_timeout`] of the
+/// request that opens a connection.
+///
+/// Used by all executors that build their own client, see
+/// [`crate::HyperExecutor::new`].
+#[derive(Clone, Debug)]
+pub struct ConnectTimeout<C> {
+    inner: C,
+}
+
+impl<C> ConnectTimeout<C> {
+    pub(crate) fn new(inner: C) -> Self {
+        Self { inner }
+    }
+}
+
+impl<C> Service<Uri> for ConnectTimeout<C>
+where
+    C: Service<Uri>,
+    C::Future: Send + 'static,
+    C::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    type Response = C::Response;
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let timeout = CONNECT_TIMEOUT.try_with(|timeout| *timeout).ok().flatten();
+        let fut = self.inner.call(uri);
+        Box::pin(async move {
+            match timeout {
+                Some(timeout) => tokio::time::timeout(timeout, fut)
+                    .await
+                    .map_err(|_elapsed| connect_timeout_error())?
+                    .map_err(Into::into),
+                None => fut.await.map_err(Into::into),
+            }
+        })
+    }
+}
@@ -1,19 +1,205 @@
-use std::task::Poll;
+mod connect;
+mod pool;
 
-use anyhttp::{DynChunksStream, HttpError, Tapper};
+use std::{
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::Poll,
+};
+
+use anyhttp::{
+    cancel::CancelToken, compression::Encoding, ConnectTo, DynChunksStream, HttpError, PoolConfig,
+    PoolStats, Respond, Tapper,
+};
 use futures_util::{future::BoxFuture, TryFutureExt, TryStreamExt};
 
+use self::pool::{ActiveBody, ActiveGuard, PoolTracker};
+pub use self::{
+    connect::ConnectTimeout,
+    pool::{TrackedConnection, TrackedConnector},
+};
+
+/// The smallest buffer size hyper accepts for HTTP/1 connections.
+const MIN_HEADER_SIZE: usize = 8192;
+
+/// Executor for [hyper](https://docs.rs/hyper).
+///
+/// Requests with [`anyhttp::RequestPre::connect_to`] are sent over a new
+/// connection that is not pooled. This is only supported for `http://` URIs.
+///
+/// Sync streaming request bodies ([`anyhttp::RequestBody::Read`]) are
+/// buffered before they are sent.
+///
+/// See [`HttpVersionPolicy`] for how the request version is handled.
 #[derive(Clone)]
 pub struct HyperExecutor<C> {
-    client: hyper::Client<C>,
+    client: Arc<dyn SendRequest>,
+    pool: Option<Arc<PoolTracker>>,
+    /// Headers for plain http requests sent through a proxy.
+    proxy_headers: Option<http::HeaderMap>,
+    version: HttpVersionPolicy,
+    /// Applied to connections opened for `connect_to`, which bypass the
+    /// client.
+    max_header_size: Option<usize>,
+    /// The builder and connector of `client`, to rebuild it with new
+    /// settings. The connector is `None` for clients built elsewhere.
+    builder: hyper::client::Builder,
+    connector: Option<C>,
+}
+
+/// The HTTP versions a [`HyperExecutor`] uses.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HttpVersionPolicy {
+    /// Use whatever the hyper client is configured for: HTTP/1.1, or HTTP/2
+    /// if the connector negotiates it with ALPN.
+    ///
+    /// Requests with version `HTTP/2` fail if the connection is HTTP/1.
+    /// Connections opened for [`anyhttp::RequestPre::connect_to`] use HTTP/2
+    /// with prior knowledge for such requests.
+    #[default]
+    Auto,
+    /// Only use HTTP/1.x. Requests with version `HTTP/2` fail without being
+    /// sent.
+    ///
+    /// The connector must not negotiate HTTP/2 with ALPN.
+    Http1Only,
+    /// Use HTTP/2 for all requests, regardless of the request version.
+    ///
+    /// Plain `http://` connections use prior knowledge (h2c), so the server
+    /// must accept HTTP/2 without an upgrade.
+    #[cfg(feature = "http2")]
+    Http2Only,
+}
+
+impl HttpVersionPolicy {
+    /// Check the request version, and return whether a new connection must
+    /// use HTTP/2.
+    fn check(self, version: http::Version) -> Result<bool, HttpError> {
+        match self {
+            Self::Auto => Ok(version == http::Version::HTTP_2),
+            Self::Http1Only if version == http::Version::HTTP_2 => Err(HttpError::new_custom(
+                "HTTP/2 requests are not supported by an HTTP/1 only executor",
+            )),
+            Self::Http1Only => Ok(false),
+            #[cfg(feature = "http2")]
+            Self::Http2Only => Ok(true),
+        }
+    }
+}
+
+/// A hyper client, with the connector type erased.
+trait SendRequest: Send + Sync {
+    fn request(&self, request: hyper::Request<hyper::Body>) -> hyper::client::ResponseFuture;
+}
+
+impl<C> SendRequest for hyper::Client<C>
+where
+    C: hyper::client::connect::Connect + Send + Sync + Clone + 'static,
+{
+    fn request(&self, request: hyper::Request<hyper::Body>) -> hyper::client::ResponseFuture {
+        hyper::Client::request(self, request)
+    }
 }
 
 impl<C> HyperExecutor<C>
 where
     C: hyper::client::connect::Connect + Send + Sync + Clone + 'static,
+    ConnectTimeout<C>: hyper::client::connect::Connect + Send + Sync + Clone + 'static,
 {
+    /// Create an executor from a client built elsewhere.
+    ///
+    /// The connector of the client can't be wrapped, so
+    /// [`anyhttp::RequestPre::connect_timeout`] is only honored for
+    /// [`anyhttp::RequestPre::connect_to`] requests. The other constructors
+    /// honor it for all requests.
     pub fn new(client: hyper::Client<C>) -> Self {
-        Self { client }
+        Self {
+            client: Arc::new(client),
+            pool: None,
+            proxy_headers: None,
+            version: HttpVersionPolicy::Auto,
+            max_header_size: None,
+            builder: hyper::Client::builder(),
+            connector: None,
+        }
+    }
+
+    /// Create an executor from a builder, which can be rebuilt with other
+    /// settings, see [`Self::max_header_size`].
+    fn from_builder(builder: hyper::client::Builder, connector: C) -> Self {
+        Self {
+            client: Arc::new(builder.build(ConnectTimeout::new(connector.clone()))),
+            pool: None,
+            proxy_headers: None,
+            version: HttpVersionPolicy::Auto,
+            max_header_size: None,
+            builder,
+            connector: Some(connector),
+        }
+    }
+
+    /// Create an executor that uses the given HTTP versions.
+    ///
+    /// Configures `builder` accordingly, for example with
+    /// `hyper::client::Builder::http2_only` for
+    /// [`HttpVersionPolicy::Http2Only`].
+    pub fn new_with_version(
+        #[allow(unused_mut)] mut builder: hyper::client::Builder,
+        connector: C,
+        version: HttpVersionPolicy,
+    ) -> Self {
+        #[cfg(feature = "http2")]
+        builder.http2_only(version == HttpVersionPolicy::Http2Only);
+        Self {
+            version,
+            ..Self::from_builder(builder, connector)
+        }
+    }
+
+    /// Create an executor with the given pool settings.
+    ///
+    /// hyper doesn't limit the total number of idle connections, so
+    /// [`PoolConfig::max_idle`] is ignored.
+    pub fn new_with_pool_config(
+        mut builder: hyper::client::Builder,
+        connector: C,
+        config: PoolConfig,
+    ) -> Self {
+        if let Some(max) = config.max_idle_per_host {
+            builder.pool_max_idle_per_host(max);
+        }
+        if let Some(timeout) = config.idle_timeout {
+            builder.pool_idle_timeout(timeout);
+        }
+        Self::from_builder(builder, connector)
+    }
+
+    /// Limit the size of HTTP/1 response headers.
+    ///
+    /// Responses with a larger header section fail with an
+    /// [`HttpError::is_headers_too_large`] error, so a malicious server
+    /// can't make the client buffer megabytes of headers.
+    ///
+    /// This sets `hyper::client::Builder::http1_max_buf_size` and rebuilds
+    /// the client, so it should be called before sending requests. The
+    /// limit is rounded up to hyper's minimum of 8192 bytes. hyper's default
+    /// is about 400 KiB. HTTP/2 header lists are limited by the `h2`
+    /// defaults.
+    ///
+    /// Executors created from a `hyper::Client` with [`Self::new`] can't be
+    /// rebuilt, so the limit only applies to connections opened for
+    /// [`anyhttp::RequestPre::connect_to`]. Configure the client instead.
+    ///
+    /// Other executors enforce their own limits; ureq for example has fixed
+    /// limits on the size and number of header fields.
+    pub fn max_header_size(mut self, max: usize) -> Self {
+        let max = max.max(MIN_HEADER_SIZE);
+        self.max_header_size = Some(max);
+        self.builder.http1_max_buf_size(max);
+        if let Some(connector) = &self.connector {
+            self.client = Arc::new(self.builder.build(ConnectTimeout::new(connector.clone())));
+        }
+        self
     }
 
     pub fn into_client(self) -> anyhttp::Client<Self> {
@@ -21,24 +207,166 @@ where
     }
 }
 
-impl<C> From<hyper::Client<C>> for HyperExecutor<C> {
+impl<C> HyperExecutor<TrackedConnector<C>>
+where
+    TrackedConnector<C>: hyper::client::connect::Connect + Send + Sync + Clone + 'static,
+    ConnectTimeout<TrackedConnector<C>>:
+        hyper::client::connect::Connect + Send + Sync + Clone + 'static,
+{
+    /// Create an executor that tracks connection pool statistics.
+    ///
+    /// See [`anyhttp::Client::pool_stats`].
+    /// hyper doesn't expose its pool, so the statistics are best-effort:
+    /// connections are counted by wrapping the connector, and a connection
+    /// is counted as in use while a response from its host is not finished.
+    pub fn new_with_pool_stats(builder: hyper::client::Builder, connector: C) -> Self {
+        let tracker = Arc::new(PoolTracker::default());
+        let connector = TrackedConnector::new(connector, tracker.clone());
+        Self {
+            pool: Some(tracker),
+            ..Self::from_builder(builder, connector)
+        }
+    }
+}
+
+#[cfg(feature = "proxy")]
+impl
+    HyperExecutor<
+        hyper_proxy::ProxyConnector<hyper_tls::HttpsConnector<hyper::client::HttpConnector>>,
+    >
+{
+    /// Create an executor that sends all requests through a proxy.
+    ///
+    /// Both `http://` and `https://` proxies are supported.
+    /// `https` targets are tunneled with `CONNECT`.
+    /// The optional credentials are sent to the proxy with basic auth.
+    pub fn with_proxy(
+        proxy_uri: http::Uri,
+        credentials: Option<(&str, &str)>,
+    ) -> Result<Self, HttpError> {
+        use headers::HeaderMapExt;
+
+        let mut proxy = hyper_proxy::Proxy::new(hyper_proxy::Intercept::All, proxy_uri);
+        if let Some((username, password)) = credentials {
+            let auth = headers::Authorization::basic(username, password);
+            let mut headers = http::HeaderMap::new();
+            headers.typed_insert(headers::ProxyAuthorization(auth.0));
+            for (name, value) in headers.into_iter() {
+                // The first item always has a name.
+                proxy.set_header(name.unwrap(), value);
+            }
+        }
+        let proxy_headers = proxy.headers().clone();
+
+        let connector =
+            hyper_proxy::ProxyConnector::from_proxy(hyper_tls::HttpsConnector::new(), proxy)
+                .map_err(|err| {
+                    HttpError::new_io(err, Some("could not create proxy connector".to_string()))
+                })?;
+
+        Ok(Self {
+            proxy_headers: Some(proxy_headers),
+            ..Self::from_builder(hyper::Client::builder(), connector)
+        })
+    }
+}
+
+#[cfg(feature = "tls")]
+impl HyperExecutor<hyper_tls::HttpsConnector<hyper::client::HttpConnector>> {
+    /// Create an executor for `http` and `https` with the given TLS
+    /// settings. See [`https_connector`].
+    pub fn new_with_tls(
+        builder: hyper::client::Builder,
+        config: &anyhttp::TlsConfig,
+    ) -> Result<Self, HttpError> {
+        Ok(Self::from_builder(builder, https_connector(config)?))
+    }
+}
+
+/// Create a connector for `http` and `https` with the given TLS settings,
+/// for example to combine them with [`HyperExecutor::new_with_pool_config`].
+///
+/// TLS is provided by native-tls. The custom root certificates are trusted
+/// in addition to the ones of the system.
+/// The protocol negotiated with ALPN is not reported to hyper, so offering
+/// `h2` doesn't switch connections to HTTP/2.
+#[cfg(feature = "tls")]
+pub fn https_connector(
+    config: &anyhttp::TlsConfig,
+) -> Result<hyper_tls::HttpsConnector<hyper::client::HttpConnector>, HttpError> {
+    let mut builder = native_tls::TlsConnector::builder();
+    for cert in &config.root_certs {
+        let cert = native_tls::Certificate::from_der(cert.der())
+            .map_err(|err| HttpError::new_custom_with_cause("invalid root certificate", err))?;
+        builder.add_root_certificate(cert);
+    }
+    if let Some(identity) = &config.identity {
+        let chain = identity
+            .certs()
+            .iter()
+            .map(|cert| cert.to_pem())
+            .collect::<String>();
+        let identity =
+            native_tls::Identity::from_pkcs8(chain.as_bytes(), identity.key_pem().as_bytes())
+                .map_err(|err| HttpError::new_custom_with_cause("invalid client identity", err))?;
+        builder.identity(identity);
+    }
+    if !config.alpn_protocols.is_empty() {
+        let protocols = config
+            .alpn_protocols
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        builder.request_alpns(&protocols);
+    }
+    if config.accepts_invalid_certs() {
+        builder.danger_accept_invalid_certs(true);
+    }
+    let tls = builder
+        .build()
+        .map_err(|err| HttpError::new_custom_with_cause("could not create TLS connector", err))?;
+
+    let mut http = hyper::client::HttpConnector::new();
+    http.enforce_http(false);
+    Ok(hyper_tls::HttpsConnector::from((http, tls.into())))
+}
+
+impl<C> From<hyper::Client<C>> for HyperExecutor<C>
+where
+    C: hyper::client::connect::Connect + Send + Sync + Clone + 'static,
+{
     fn from(client: hyper::Client<C>) -> Self {
-        Self { client }
+        Self {
+            client: Arc::new(client),
+            pool: None,
+            proxy_headers: None,
+            version: HttpVersionPolicy::Auto,
+            max_header_size: None,
+            builder: hyper::Client::builder(),
+            connector: None,
+        }
     }
 }
 
-pub struct ResponseBody(pub hyper::Body);
+/// The body of a response.
+///
+/// The second field receives the trailers once the original body ended, if
+/// the executor wrapped it in a stream.
+pub struct ResponseBody(pub hyper::Body, Option<Trailers>);
+
+impl From<hyper::Body> for ResponseBody {
+    fn from(body: hyper::Body) -> Self {
+        Self(body, None)
+    }
+}
 
 impl anyhttp::Respond for ResponseBody {
     type Chunks = DynChunksStream;
     type BytesOutput = BoxFuture<'static, Result<Vec<u8>, anyhttp::HttpError>>;
-    type Reader = Box<dyn std::io::Read>;
+    type Reader = Pin<Box<dyn futures_util::AsyncRead + Send>>;
 
     fn into_chunks(self) -> Self::Chunks {
-        let s = self.0.map_ok(|b| b.to_vec()).map_err(|err| {
-            // TODO: proper error mapping
-            HttpError::new_response_read(None, err)
-        });
+        let s = self.0.map_ok(|b| b.to_vec()).map_err(map_body_error);
         Box::pin(s)
     }
 
@@ -46,36 +374,87 @@ impl anyhttp::Respond for ResponseBody {
         (*self).into_chunks()
     }
 
+    #[cfg(feature = "bytes")]
+    fn into_bytes_chunks_boxed(
+        self: Box<Self>,
+    ) -> futures_util::stream::BoxStream<'static, Result<hyper::body::Bytes, HttpError>> {
+        let s = self.0.map_err(map_body_error);
+        Box::pin(s)
+    }
+
     fn bytes(self) -> Self::BytesOutput {
         let f = hyper::body::to_bytes(self.0)
             .map_ok(|b| b.to_vec())
-            .map_err(|err| {
-                // FIXME: proper error mapping
-                anyhttp::HttpError::new_custom_with_cause("could not read response", Box::new(err))
-            });
+            .map_err(map_body_error);
         Box::pin(f)
     }
 
     fn bytes_boxed(self: Box<Self>) -> Self::BytesOutput {
-        self.bytes()
+        (*self).bytes()
     }
 
     fn reader(self) -> Self::Reader {
-        todo!()
+        let chunks = self.into_chunks().map_err(std::io::Error::other);
+        Box::pin(chunks.into_async_read())
     }
 
     fn reader_boxed(self: Box<Self>) -> Self::Reader {
-        todo!()
+        (*self).reader()
+    }
+
+    #[cfg(feature = "bytes")]
+    fn bytes_buf_boxed(self: Box<Self>) -> anyhttp::HttpFuture<'static, hyper::body::Bytes> {
+        let f = hyper::body::to_bytes(self.0).map_err(map_body_error);
+        Box::pin(f)
+    }
+
+    fn trailers_boxed(self: Box<Self>) -> anyhttp::HttpFuture<'static, Option<http::HeaderMap>> {
+        use hyper::body::HttpBody;
+
+        let ResponseBody(mut body, trailers) = *self;
+        Box::pin(async move {
+            while let Some(chunk) = body.data().await {
+                chunk.map_err(map_body_error)?;
+            }
+            match trailers {
+                Some(trailers) => Ok(trailers.lock().unwrap().take()),
+                None => body.trailers().await.map_err(map_body_error),
+            }
+        })
     }
 }
 
 pin_project_lite::pin_project! {
      #[project = ResponseFutureProject]
     pub enum ResponseFuture {
-        Hyper { #[pin] fut: hyper::client::ResponseFuture, tap: Option<Tapper>, uri: http::Uri },
+        Hyper {
+            #[pin]
+            fut: hyper::client::ResponseFuture,
+            tap: Option<Tapper>,
+            uri: http::Uri,
+            decompress: bool,
+            active: Option<ActiveGuard>,
+            cancel: Option<CancelToken>,
+            timeout: Option<Pin<Box<tokio::time::Sleep>>>,
+            connect_timeout: Option<std::time::Duration>,
+        },
+        Direct {
+            #[pin]
+            fut: BoxFuture<'static, Result<hyper::Response<hyper::Body>, HttpError>>,
+            tap: Option<Tapper>,
+            uri: http::Uri,
+            decompress: bool,
+            cancel: Option<CancelToken>,
+            timeout: Option<Pin<Box<tokio::time::Sleep>>>,
+        },
         Ready{
             res: Option<Result<anyhttp::Response<ResponseBody>, anyhttp::HttpError>>,
-        }
+        },
+        // A response future running in the tracing span of the request.
+        Instrumented {
+            #[pin]
+            fut: BoxFuture<'static, Result<anyhttp::Response<ResponseBody>, HttpError>>,
+        },
     }
 }
 
@@ -87,28 +466,57 @@ impl std::future::Future for ResponseFuture {
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Self::Output> {
         match self.project() {
-            ResponseFutureProject::Hyper { fut, tap, uri } => match fut.poll(cx) {
-                Poll::Ready(res) => {
-                    let res = res
-                        .map(|res| {
-                            let res = anyhttp::Response::from(res).map_body(ResponseBody);
-                            let (mut res, body) = res.take_body();
-                            res.uri = Some(uri.clone());
-                            if let Some(f) = tap.take() {
-                                f(&mut res);
-                            }
-
-                            res.map_body(move |_| body)
-                        })
-                        .map_err(|err| {
-                            // FIXME: proper error mapping
-                            anyhttp::HttpError::new_custom_with_cause("hyper error", err)
-                        });
-
-                    Poll::Ready(res)
+            ResponseFutureProject::Hyper {
+                fut,
+                tap,
+                uri,
+                decompress,
+                active,
+                cancel,
+                timeout,
+                connect_timeout,
+            } => {
+                if is_cancelled(cancel, cx) {
+                    return Poll::Ready(Err(HttpError::new_cancelled()));
                 }
-                Poll::Pending => Poll::Pending,
-            },
+                match connect::with_connect_timeout(*connect_timeout, || fut.poll(cx)) {
+                    Poll::Ready(res) => Poll::Ready(res.map_err(map_error).and_then(|res| {
+                        finish_response(
+                            res,
+                            tap.take(),
+                            uri,
+                            *decompress,
+                            active.take(),
+                            timeout.take(),
+                        )
+                    })),
+                    Poll::Pending if is_timed_out(timeout, cx) => {
+                        Poll::Ready(Err(new_timeout_error()))
+                    }
+                    Poll::Pending => Poll::Pending,
+                }
+            }
+            ResponseFutureProject::Direct {
+                fut,
+                tap,
+                uri,
+                decompress,
+                cancel,
+                timeout,
+            } => {
+                if is_cancelled(cancel, cx) {
+                    return Poll::Ready(Err(HttpError::new_cancelled()));
+                }
+                match fut.poll(cx) {
+                    Poll::Ready(res) => Poll::Ready(res.and_then(|res| {
+                        finish_response(res, tap.take(), uri, *decompress, None, timeout.take())
+                    })),
+                    Poll::Pending if is_timed_out(timeout, cx) => {
+                        Poll::Ready(Err(new_timeout_error()))
+                    }
+                    Poll::Pending => Poll::Pending,
+                }
+            }
             ResponseFutureProject::Ready { res } => {
                 if let Some(res) = res.take() {
                     Poll::Ready(res)
@@ -117,10 +525,338 @@ impl std::future::Future for ResponseFuture {
                     Poll::Pending
                 }
             }
+            ResponseFutureProject::Instrumented { fut } => fut.poll(cx),
+        }
+    }
+}
+
+/// Check the cancellation token, and register the task to be woken on
+/// cancellation.
+fn is_cancelled(cancel: &Option<CancelToken>, cx: &mut std::task::Context<'_>) -> bool {
+    cancel
+        .as_ref()
+        .is_some_and(|cancel| cancel.poll_cancelled(cx).is_ready())
+}
+
+/// Check the timeout, and register the task to be woken when it elapses.
+fn is_timed_out(
+    timeout: &mut Option<Pin<Box<tokio::time::Sleep>>>,
+    cx: &mut std::task::Context<'_>,
+) -> bool {
+    timeout
+        .as_mut()
+        .is_some_and(|sleep| std::future::Future::poll(sleep.as_mut(), cx).is_ready())
+}
+
+/// Trailers of a body that was wrapped in a stream, which
+/// `hyper::Body::wrap_stream` doesn't forward.
+type Trailers = Arc<Mutex<Option<http::HeaderMap>>>;
+
+pin_project_lite::pin_project! {
+    /// Body stream that stores the trailers of the inner body after its data.
+    struct TrailersBody {
+        #[pin]
+        inner: hyper::Body,
+        trailers: Trailers,
+        done: bool,
+    }
+}
+
+impl futures_util::Stream for TrailersBody {
+    type Item = Result<hyper::body::Bytes, hyper::Error>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        use hyper::body::HttpBody;
+
+        let mut this = self.project();
+        if *this.done {
+            return Poll::Ready(None);
+        }
+        if let Some(item) = std::task::ready!(this.inner.as_mut().poll_data(cx)) {
+            return Poll::Ready(Some(item));
+        }
+        let trailers = std::task::ready!(this.inner.poll_trailers(cx));
+        *this.done = true;
+        match trailers {
+            Ok(trailers) => {
+                *this.trailers.lock().unwrap() = trailers;
+                Poll::Ready(None)
+            }
+            Err(err) => Poll::Ready(Some(Err(err))),
         }
     }
 }
 
+pin_project_lite::pin_project! {
+    /// Body stream that fails when the request timeout expires.
+    struct TimeoutBody {
+        #[pin]
+        inner: hyper::Body,
+        timeout: Option<Pin<Box<tokio::time::Sleep>>>,
+    }
+}
+
+impl TimeoutBody {
+    fn new(inner: hyper::Body, timeout: Pin<Box<tokio::time::Sleep>>) -> Self {
+        Self {
+            inner,
+            timeout: Some(timeout),
+        }
+    }
+}
+
+impl futures_util::Stream for TimeoutBody {
+    type Item = Result<hyper::body::Bytes, HttpError>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        if this.timeout.is_none() {
+            // The body ended with the timeout error.
+            return Poll::Ready(None);
+        }
+        match this.inner.poll_next(cx) {
+            Poll::Ready(item) => Poll::Ready(item.map(|res| res.map_err(map_body_error))),
+            Poll::Pending if is_timed_out(this.timeout, cx) => {
+                *this.timeout = None;
+                Poll::Ready(Some(Err(HttpError::new_timeout(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    "response body not received within the request timeout",
+                )))))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Errors of body streams wrapped by the executor, like timeouts, are
+/// passed through.
+fn map_body_error(err: hyper::Error) -> HttpError {
+    let wrapped = std::error::Error::source(&err).is_some_and(|cause| cause.is::<HttpError>());
+    if wrapped {
+        if let Some(Ok(err)) = err.into_cause().map(|cause| cause.downcast::<HttpError>()) {
+            return *err;
+        }
+        unreachable!("the cause was checked to be an HttpError");
+    }
+    HttpError::new_response_read(None, err)
+}
+
+fn new_timeout_error() -> HttpError {
+    HttpError::new_timeout(std::io::Error::new(
+        std::io::ErrorKind::TimedOut,
+        "no response within the request timeout",
+    ))
+}
+
+fn finish_response(
+    res: hyper::Response<hyper::Body>,
+    tap: Option<Tapper>,
+    uri: &http::Uri,
+    decompress: bool,
+    active: Option<ActiveGuard>,
+    timeout: Option<Pin<Box<tokio::time::Sleep>>>,
+) -> Result<anyhttp::Response<ResponseBody>, HttpError> {
+    let (mut res, mut body) = anyhttp::Response::from(res).take_body();
+    // hyper only keeps the phrase if it is not the canonical one.
+    if let Some(reason) = res.extensions.remove::<hyper::ext::ReasonPhrase>() {
+        let reason = String::from_utf8_lossy(reason.as_bytes()).into_owned();
+        res.extensions.insert(anyhttp::ReasonPhrase(reason));
+    }
+    let encoding = if decompress {
+        Encoding::take_from_headers(&mut res.headers)
+    } else {
+        None
+    };
+    let mut trailers = None;
+    if active.is_some() || encoding.is_some() || timeout.is_some() {
+        let slot = Trailers::default();
+        body = hyper::Body::wrap_stream(TrailersBody {
+            inner: body,
+            trailers: slot.clone(),
+            done: false,
+        });
+        trailers = Some(slot);
+    }
+    if let Some(active) = active {
+        body = hyper::Body::wrap_stream(ActiveBody::new(body, active));
+    }
+    if let Some(encoding) = encoding {
+        let chunks = ResponseBody::from(body).into_chunks();
+        body = hyper::Body::wrap_stream(encoding.decode_stream(chunks));
+    }
+    if let Some(timeout) = timeout {
+        body = hyper::Body::wrap_stream(TimeoutBody::new(body, timeout));
+    }
+    let body = ResponseBody(body, trailers);
+    res.uri = Some(uri.clone());
+    if let Some(f) = tap {
+        anyhttp::run_tap(&f, &mut res)?;
+    }
+
+    Ok(res.map_body(move |_| body))
+}
+
+/// Send a request over a new connection to `target`.
+///
+/// hyper's pool is keyed by the URI and its connector only sees the URI, so
+/// these connections bypass the client and are not pooled.
+async fn send_direct(
+    target: ConnectTo,
+    mut request: hyper::Request<hyper::Body>,
+    http2: bool,
+    max_header_size: Option<usize>,
+    connect_timeout: Option<std::time::Duration>,
+) -> Result<hyper::Response<hyper::Body>, HttpError> {
+    if request.uri().scheme() == Some(&http::uri::Scheme::HTTPS) {
+        return Err(HttpError::new_custom(
+            "connect_to is not supported for https requests by the hyper executor",
+        ));
+    }
+
+    // Unlike `hyper::Client`, a plain HTTP/1 connection sends the URI as is
+    // and doesn't add a `Host` header. HTTP/2 needs the absolute URI.
+    if !http2 {
+        if let Some(host) = request.uri().host() {
+            if !request.headers().contains_key(http::header::HOST) {
+                let host = match request.uri().port_u16() {
+                    Some(port) => format!("{host}:{port}"),
+                    None => host.to_string(),
+                };
+                let value = http::HeaderValue::from_str(&host)
+                    .map_err(|err| HttpError::new_http(err.into()))?;
+                request.headers_mut().insert(http::header::HOST, value);
+            }
+        }
+        let mut parts = http::uri::Parts::default();
+        parts.path_and_query = Some(
+            request
+                .uri()
+                .path_and_query()
+                .cloned()
+                .unwrap_or_else(|| http::uri::PathAndQuery::from_static("/")),
+        );
+        *request.uri_mut() =
+            http::Uri::from_parts(parts).map_err(|err| HttpError::new_http(err.into()))?;
+    }
+
+    match target {
+        ConnectTo::Addr(addr) => {
+            let io =
+                connect_with_timeout(tokio::net::TcpStream::connect(addr), connect_timeout).await?;
+            send_on(io, request, http2, max_header_size).await
+        }
+        #[cfg(unix)]
+        ConnectTo::Unix(path) => {
+            let io = connect_with_timeout(tokio::net::UnixStream::connect(path), connect_timeout)
+                .await?;
+            send_on(io, request, http2, max_header_size).await
+        }
+        #[cfg(not(unix))]
+        ConnectTo::Unix(_) => Err(HttpError::new_custom(
+            "unix sockets are not supported on this platform",
+        )),
+    }
+}
+
+/// Open a connection, failing with a timeout error after `timeout`.
+async fn connect_with_timeout<F, T>(
+    connect: F,
+    timeout: Option<std::time::Duration>,
+) -> Result<T, HttpError>
+where
+    F: std::future::Future<Output = Result<T, std::io::Error>>,
+{
+    let res = match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, connect)
+            .await
+            .map_err(|_elapsed| HttpError::new_timeout(connect::connect_timeout_error()))?,
+        None => connect.await,
+    };
+    res.map_err(HttpError::new_connect)
+}
+
+async fn send_on<T>(
+    io: T,
+    request: hyper::Request<hyper::Body>,
+    http2: bool,
+    max_header_size: Option<usize>,
+) -> Result<hyper::Response<hyper::Body>, HttpError>
+where
+    T: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send + 'static,
+{
+    #[allow(unused_mut)]
+    let mut builder = hyper::client::conn::Builder::new();
+    #[cfg(feature = "http2")]
+    builder.http2_only(http2);
+    #[cfg(not(feature = "http2"))]
+    if http2 {
+        return Err(HttpError::new_custom(
+            "HTTP/2 requires the http2 feature of the hyper executor",
+        ));
+    }
+    if let Some(max) = max_header_size {
+        builder.http1_max_buf_size(max);
+    }
+    let (mut sender, conn) = builder.handshake(io).await.map_err(map_error)?;
+    tokio::spawn(async move {
+        // Errors surface in the response or its body.
+        let _ = conn.await;
+    });
+    sender.send_request(request).await.map_err(map_error)
+}
+
+/// Enforce `RequestPre::max_request_body_size`.
+///
+/// Bodies of unknown length are checked while they are sent.
+fn limit_body(body: hyper::Body, limit: u64) -> Result<hyper::Body, HttpError> {
+    if let Some(len) = hyper::body::HttpBody::size_hint(&body).exact() {
+        if len > limit {
+            return Err(HttpError::new_request_too_large(limit));
+        }
+        return Ok(body);
+    }
+    let mut sent = 0u64;
+    let chunks = body
+        .map_err(Into::into)
+        .and_then(move |chunk: hyper::body::Bytes| {
+            sent += chunk.len() as u64;
+            let res: Result<_, Box<dyn std::error::Error + Send + Sync>> = if sent > limit {
+                Err(Box::new(HttpError::new_request_too_large(limit)))
+            } else {
+                Ok(chunk)
+            };
+            futures_util::future::ready(res)
+        });
+    Ok(hyper::Body::wrap_stream(chunks))
+}
+
+fn map_error(err: hyper::Error) -> HttpError {
+    // Connect timeouts are connect errors for hyper.
+    let mut source = std::error::Error::source(&err);
+    while let Some(inner) = source {
+        if let Some(io) = inner.downcast_ref::<std::io::Error>() {
+            if io.kind() == std::io::ErrorKind::TimedOut {
+                return HttpError::new_timeout(err);
+            }
+        }
+        source = inner.source();
+    }
+    if err.is_connect() {
+        return HttpError::new_connect(err);
+    }
+    if err.is_parse_too_large() {
+        return HttpError::new_headers_too_large(err);
+    }
+    // FIXME: more detailed mapping
+    HttpError::new_custom_with_cause("hyper error", err)
+}
+
 impl<C> anyhttp::HttpExecutor for HyperExecutor<C>
 where
     C: hyper::client::connect::Connect + Clone + Send + Sync + 'static,
@@ -133,7 +869,23 @@ where
         match body {
             anyhttp::RequestBody::Empty => hyper::Body::empty(),
             anyhttp::RequestBody::Bytes(b) => hyper::Body::from(b),
-            anyhttp::RequestBody::Read(_) => todo!(),
+            // Readers are not `Send`, so they can't be streamed from another
+            // thread and are buffered instead.
+            anyhttp::RequestBody::Read(mut reader) => {
+                let mut bytes = Vec::new();
+                match reader.read_to_end(&mut bytes) {
+                    Ok(_) => hyper::Body::from(bytes),
+                    // The error fails the request when the body is sent.
+                    Err(err) => {
+                        let err =
+                            HttpError::new_io(err, Some("could not read request body".to_string()));
+                        hyper::Body::wrap_stream(futures_util::stream::once(async move {
+                            Err::<hyper::body::Bytes, _>(err)
+                        }))
+                    }
+                }
+            }
+            anyhttp::RequestBody::Stream(stream) => hyper::Body::wrap_stream(stream.into_stream()),
         }
     }
 
@@ -143,13 +895,107 @@ where
         }
     }
 
-    fn execute(&self, pre: anyhttp::RequestPre<Self::RequestBody>) -> Self::Output {
+    fn execute(&self, mut pre: anyhttp::RequestPre<Self::RequestBody>) -> Self::Output {
+        let http2 = match self.version.check(pre.request.version) {
+            Ok(http2) => http2,
+            Err(err) => {
+                return ResponseFuture::Ready {
+                    res: Some(Err(err)),
+                }
+            }
+        };
         let uri = pre.request.uri.clone();
+        if let Some(proxy_headers) = &self.proxy_headers {
+            // Tunneled https requests get the headers in the CONNECT request.
+            if uri.scheme() != Some(&http::uri::Scheme::HTTPS) {
+                for (name, value) in proxy_headers {
+                    if !pre.request.headers.contains_key(name) {
+                        pre.request.headers.insert(name, value.clone());
+                    }
+                }
+            }
+        }
+        if let Some(limit) = pre.max_request_body_size {
+            match limit_body(std::mem::take(&mut pre.request.body), limit) {
+                Ok(body) => pre.request.body = body,
+                Err(err) => {
+                    return ResponseFuture::Ready {
+                        res: Some(Err(err)),
+                    }
+                }
+            }
+        }
+        // hyper omits the length of empty bodies, but servers may reject
+        // mutating requests without one.
+        let req = &mut pre.request;
+        let needs_length = matches!(
+            req.method,
+            http::Method::POST | http::Method::PUT | http::Method::PATCH
+        );
+        if needs_length
+            && hyper::body::HttpBody::size_hint(&req.body).exact() == Some(0)
+            && !req.headers.contains_key(http::header::CONTENT_LENGTH)
+            && !req.headers.contains_key(http::header::TRANSFER_ENCODING)
+        {
+            req.headers.insert(
+                http::header::CONTENT_LENGTH,
+                http::HeaderValue::from_static("0"),
+            );
+        }
+        // The timeout covers the request until the response head is
+        // received, and the remaining time is carried into the body.
+        let timeout = match pre.remaining_timeout() {
+            Ok(timeout) => timeout.map(|timeout| Box::pin(tokio::time::sleep(timeout))),
+            Err(err) => {
+                return ResponseFuture::Ready {
+                    res: Some(Err(err)),
+                }
+            }
+        };
+        // HEAD responses have an empty body, which can't be decoded.
+        let decompress = pre.decompress && pre.request.method != http::Method::HEAD;
+        if let Some(target) = pre.connect_to {
+            return ResponseFuture::Direct {
+                fut: Box::pin(send_direct(
+                    target,
+                    pre.request.into(),
+                    http2,
+                    self.max_header_size,
+                    pre.connect_timeout,
+                )),
+                tap: pre.tap,
+                uri,
+                decompress,
+                cancel: pre.cancel,
+                timeout,
+            };
+        }
+        let active = self.pool.as_ref().map(|p| p.begin_request(&uri));
         let fut = self.client.request(pre.request.into());
         ResponseFuture::Hyper {
             fut,
             tap: pre.tap,
             uri,
+            decompress,
+            active,
+            cancel: pre.cancel,
+            timeout,
+            connect_timeout: pre.connect_timeout,
+        }
+    }
+
+    fn pool_stats(&self) -> Option<PoolStats> {
+        self.pool.as_ref().map(|p| p.stats())
+    }
+
+    #[cfg(feature = "tracing")]
+    fn instrument_output(
+        &self,
+        output: Self::Output,
+        span: anyhttp::trace::RequestSpan,
+    ) -> Self::Output {
+        ResponseFuture::Instrumented {
+            fut: Box::pin(span.instrument(output)),
         }
     }
 }
@@ -161,6 +1007,315 @@ mod tests {
     #[tokio::test]
     async fn test_hyper_client() {
         let exec = HyperExecutor::from(hyper::client::Client::new());
+        anyhttp::test::test_async_executor_strict(exec).await;
+
+        let exec = HyperExecutor::from(hyper::client::Client::new());
+        anyhttp::test::test_async_executor(exec).await;
+
+        let exec = HyperExecutor::new_with_pool_config(
+            hyper::Client::builder(),
+            hyper::client::HttpConnector::new(),
+            PoolConfig::new().max_idle_per_host(2),
+        );
+        anyhttp::test::test_async_connect_to(exec).await;
+
+        let exec = HyperExecutor::new_with_pool_stats(
+            hyper::Client::builder(),
+            hyper::client::HttpConnector::new(),
+        );
         anyhttp::test::test_async_executor(exec).await;
+
+        #[cfg(feature = "proxy")]
+        {
+            let proxy_uri = format!("http://{}", anyhttp::test::TEST_URL);
+            let exec =
+                HyperExecutor::with_proxy(proxy_uri.parse().unwrap(), Some(("user", "pass")))
+                    .unwrap();
+            anyhttp::test::test_async_proxy_executor(exec).await;
+        }
+    }
+
+    /// Serve the request version over HTTP/2 with prior knowledge.
+    fn spawn_h2c_server() -> std::net::SocketAddr {
+        use hyper::service::{make_service_fn, service_fn};
+
+        let make_service = make_service_fn(|_| async {
+            Ok::<_, std::convert::Infallible>(service_fn(|req: hyper::Request<hyper::Body>| {
+                let version = format!("{:?}", req.version());
+                async move {
+                    Ok::<_, std::convert::Infallible>(hyper::Response::new(hyper::Body::from(
+                        version,
+                    )))
+                }
+            }))
+        });
+        let server = hyper::Server::bind(&([127, 0, 0, 1], 0).into())
+            .http2_only(true)
+            .serve(make_service);
+        let addr = server.local_addr();
+        tokio::spawn(server);
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_hyper_reason_phrase() {
+        use hyper::service::{make_service_fn, service_fn};
+
+        let make_service = make_service_fn(|_| async {
+            Ok::<_, std::convert::Infallible>(service_fn(|_req| async {
+                let mut res = hyper::Response::new(hyper::Body::empty());
+                let reason = hyper::ext::ReasonPhrase::from_static(b"Everything Fine");
+                res.extensions_mut().insert(reason);
+                Ok::<_, std::convert::Infallible>(res)
+            }))
+        });
+        let server = hyper::Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+        let addr = server.local_addr();
+        tokio::spawn(server);
+
+        let client = HyperExecutor::from(hyper::Client::new()).into_client();
+        let res = client.get(format!("http://{addr}/")).send().await.unwrap();
+        assert_eq!(res.reason(), Some("Everything Fine"));
+    }
+
+    #[tokio::test]
+    async fn test_hyper_max_header_size() {
+        use hyper::service::{make_service_fn, service_fn};
+
+        let make_service = make_service_fn(|_| async {
+            Ok::<_, std::convert::Infallible>(service_fn(|_req| async {
+                let res = hyper::Response::builder()
+                    .header("x-large", "a".repeat(16 * 1024))
+                    .body(hyper::Body::empty())
+                    .unwrap();
+                Ok::<_, std::convert::Infallible>(res)
+            }))
+        });
+        let server = hyper::Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+        let addr = server.local_addr();
+        tokio::spawn(server);
+        let uri = format!("http://{addr}/");
+
+        let client = HyperExecutor::from(hyper::Client::new()).into_client();
+        let res = client.get(&uri).send().await.unwrap();
+        assert_eq!(res.header_str("x-large").map(str::len), Some(16 * 1024));
+
+        let client = HyperExecutor::new_with_pool_config(
+            hyper::Client::builder(),
+            hyper::client::HttpConnector::new(),
+            PoolConfig::new().max_idle_per_host(2),
+        )
+        .max_header_size(1024)
+        .into_client();
+        let err = client.get(&uri).send().await.err().unwrap();
+        assert!(err.is_headers_too_large(), "{err}");
+        let err = client
+            .get(&uri)
+            .connect_to(addr)
+            .send()
+            .await
+            .err()
+            .unwrap();
+        assert!(err.is_headers_too_large(), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_hyper_read_body() {
+        use anyhttp::HttpExecutor;
+
+        let exec = HyperExecutor::from(hyper::Client::new());
+        let body = anyhttp::RequestBody::Read(Box::new(std::io::Cursor::new(b"data".to_vec())));
+        let body = exec.request_body_from_generic(body);
+        assert_eq!(hyper::body::to_bytes(body).await.unwrap(), "data");
+
+        struct Failing;
+
+        impl std::io::Read for Failing {
+            fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("broken"))
+            }
+        }
+
+        let body = exec.request_body_from_generic(anyhttp::RequestBody::Read(Box::new(Failing)));
+        assert!(hyper::body::to_bytes(body).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_hyper_connect_timeout() {
+        // A connection to a local listener can't be made to stall, so the
+        // timeout is tested with a connection that never completes.
+        let connect = std::future::pending::<std::io::Result<()>>();
+        let err = connect_with_timeout(connect, Some(std::time::Duration::from_millis(10)))
+            .await
+            .err()
+            .unwrap();
+        assert!(err.is_timeout(), "{err}");
+
+        let connect =
+            std::future::ready(Err::<(), _>(std::io::ErrorKind::ConnectionRefused.into()));
+        let err = connect_with_timeout(connect, Some(std::time::Duration::from_secs(5)))
+            .await
+            .err()
+            .unwrap();
+        assert!(err.is_connect(), "{err}");
+
+        // Pooled connections are opened by the connector of the client.
+        #[derive(Clone)]
+        struct PendingConnector;
+
+        impl hyper::service::Service<http::Uri> for PendingConnector {
+            type Response = tokio::net::TcpStream;
+            type Error = std::io::Error;
+            type Future = std::future::Pending<std::io::Result<tokio::net::TcpStream>>;
+
+            fn poll_ready(
+                &mut self,
+                _cx: &mut std::task::Context<'_>,
+            ) -> Poll<Result<(), Self::Error>> {
+                Poll::Ready(Ok(()))
+            }
+
+            fn call(&mut self, _uri: http::Uri) -> Self::Future {
+                std::future::pending()
+            }
+        }
+
+        let client = HyperExecutor::new_with_pool_config(
+            hyper::Client::builder(),
+            PendingConnector,
+            PoolConfig::new(),
+        )
+        .into_client();
+        let err = client
+            .get("http://service.invalid/")
+            .connect_timeout(std::time::Duration::from_millis(10))
+            .send()
+            .await
+            .err()
+            .unwrap();
+        assert!(err.is_timeout(), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_hyper_trailers() {
+        use hyper::service::{make_service_fn, service_fn};
+
+        let make_service = make_service_fn(|_| async {
+            Ok::<_, std::convert::Infallible>(service_fn(
+                |req: hyper::Request<hyper::Body>| async move {
+                    let deflate = req.uri().path() == "/deflate";
+                    let (mut sender, body) = hyper::Body::channel();
+                    tokio::spawn(async move {
+                        let data = if deflate {
+                            // "data", compressed with zlib.
+                            vec![120, 156, 75, 73, 44, 73, 4, 0, 4, 0, 1, 155].into()
+                        } else {
+                            "data".into()
+                        };
+                        sender.send_data(data).await.unwrap();
+                        let mut trailers = http::HeaderMap::new();
+                        trailers.insert("grpc-status", http::HeaderValue::from_static("0"));
+                        sender.send_trailers(trailers).await.unwrap();
+                    });
+                    let mut res = hyper::Response::new(body);
+                    if deflate {
+                        res.headers_mut().insert(
+                            http::header::CONTENT_ENCODING,
+                            http::HeaderValue::from_static("deflate"),
+                        );
+                    }
+                    Ok::<_, std::convert::Infallible>(res)
+                },
+            ))
+        });
+        let server = hyper::Server::bind(&([127, 0, 0, 1], 0).into())
+            .http2_only(true)
+            .serve(make_service);
+        let addr = server.local_addr();
+        tokio::spawn(server);
+
+        let client = HyperExecutor::new_with_version(
+            hyper::Client::builder(),
+            hyper::client::HttpConnector::new(),
+            HttpVersionPolicy::Http2Only,
+        )
+        .into_client();
+        let res = client.get(format!("http://{addr}/")).send().await.unwrap();
+        let trailers = res.trailers_async().await.unwrap().unwrap();
+        assert_eq!(trailers["grpc-status"], "0");
+
+        // Trailers are forwarded through the bodies wrapped by the executor.
+        let res = client
+            .get(format!("http://{addr}/deflate"))
+            .timeout(std::time::Duration::from_secs(5))
+            .send()
+            .await
+            .unwrap();
+        let trailers = res.trailers_async().await.unwrap().unwrap();
+        assert_eq!(trailers["grpc-status"], "0");
+
+        let mut builder = hyper::Client::builder();
+        builder.http2_only(true);
+        let client = HyperExecutor::new_with_pool_config(
+            builder,
+            hyper::client::HttpConnector::new(),
+            PoolConfig::new(),
+        )
+        .into_client();
+        let res = client.get(format!("http://{addr}/")).send().await.unwrap();
+        let trailers = res.trailers_async().await.unwrap().unwrap();
+        assert_eq!(trailers["grpc-status"], "0");
+    }
+
+    #[tokio::test]
+    async fn test_hyper_http_version() {
+        let addr = spawn_h2c_server();
+        let uri = format!("http://{addr}/");
+
+        let client = HyperExecutor::new_with_version(
+            hyper::Client::builder(),
+            hyper::client::HttpConnector::new(),
+            HttpVersionPolicy::Http2Only,
+        )
+        .into_client();
+        let res = client.get(&uri).send().await.unwrap();
+        assert_eq!(res.version, http::Version::HTTP_2);
+        assert_eq!(res.body.bytes().await.unwrap(), b"HTTP/2.0");
+
+        // An HTTP/2 request can't be sent over the HTTP/1 connection.
+        let client = HyperExecutor::from(hyper::Client::new()).into_client();
+        let err = client
+            .get(&uri)
+            .version(http::Version::HTTP_2)
+            .send()
+            .await
+            .err()
+            .unwrap();
+        assert!(!err.is_connect(), "{err}");
+
+        // Direct connections use prior knowledge for HTTP/2 requests.
+        let res = client
+            .get("http://h2c.test/")
+            .version(http::Version::HTTP_2)
+            .connect_to(addr)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.version, http::Version::HTTP_2);
+
+        let client = HyperExecutor::new_with_version(
+            hyper::Client::builder(),
+            hyper::client::HttpConnector::new(),
+            HttpVersionPolicy::Http1Only,
+        )
+        .into_client();
+        let err = client
+            .get(&uri)
+            .version(http::Version::HTTP_2)
+            .send()
+            .await
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("HTTP/1 only"), "{err}");
     }
 }
@@ -0,0 +1,218 @@
+//! Best-effort connection pool statistics.
+//!
+//! hyper doesn't expose its pool, so connections are counted by wrapping the
+//! connector, and a connection is considered in use while a request to the
+//! host has an unfinished response.
+
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use anyhttp::{HostPoolStats, PoolStats};
+use futures_util::{future::BoxFuture, Stream};
+use hyper::{
+    client::connect::{Connected, Connection},
+    service::Service,
+    Uri,
+};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+#[derive(Default)]
+struct HostCounts {
+    open: usize,
+    active: usize,
+}
+
+#[derive(Default)]
+pub(crate) struct PoolTracker {
+    hosts: Mutex<HashMap<String, HostCounts>>,
+}
+
+impl PoolTracker {
+    pub(crate) fn stats(&self) -> PoolStats {
+        let hosts = self.hosts.lock().unwrap();
+        let hosts = hosts
+            .iter()
+            .filter(|(_, counts)| counts.open > 0)
+            .map(|(host, counts)| {
+                let in_use = counts.active.min(counts.open);
+                let stats = HostPoolStats {
+                    idle: counts.open - in_use,
+                    in_use,
+                };
+                (host.clone(), stats)
+            })
+            .collect();
+        PoolStats { hosts }
+    }
+
+    fn update(&self, host: &str, f: impl FnOnce(&mut HostCounts)) {
+        let mut hosts = self.hosts.lock().unwrap();
+        f(hosts.entry(host.to_string()).or_default());
+    }
+
+    /// Mark a request to the host as active until the guard is dropped.
+    pub(crate) fn begin_request(self: &Arc<Self>, uri: &Uri) -> ActiveGuard {
+        let host = host_key(uri);
+        self.update(&host, |c| c.active += 1);
+        ActiveGuard {
+            tracker: self.clone(),
+            host,
+        }
+    }
+}
+
+fn host_key(uri: &Uri) -> String {
+    uri.authority()
+        .map(|a| a.as_str().to_string())
+        .unwrap_or_default()
+}
+
+pub(crate) struct ActiveGuard {
+    tracker: Arc<PoolTracker>,
+    host: String,
+}
+
+impl Drop for ActiveGuard {
+    fn drop(&mut self) {
+        self.tracker.update(&self.host, |c| c.active -= 1);
+    }
+}
+
+struct OpenGuard {
+    tracker: Arc<PoolTracker>,
+    host: String,
+}
+
+impl Drop for OpenGuard {
+    fn drop(&mut self) {
+        self.tracker.update(&self.host, |c| c.open -= 1);
+    }
+}
+
+/// Connector wrapper that counts open connections.
+///
+/// See [`crate::HyperExecutor::new_with_pool_stats`].
+#[derive(Clone)]
+pub struct TrackedConnector<C> {
+    inner: C,
+    tracker: Arc<PoolTracker>,
+}
+
+impl<C> TrackedConnector<C> {
+    pub(crate) fn new(inner: C, tracker: Arc<PoolTracker>) -> Self {
+        Self { inner, tracker }
+    }
+}
+
+impl<C> Service<Uri> for TrackedConnector<C>
+where
+    C: Service<Uri>,
+    C::Future: Send + 'static,
+{
+    type Response = TrackedConnection<C::Response>;
+    type Error = C::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let host = host_key(&uri);
+        let tracker = self.tracker.clone();
+        let fut = self.inner.call(uri);
+        Box::pin(async move {
+            let inner = fut.await?;
+            tracker.update(&host, |c| c.open += 1);
+            Ok(TrackedConnection {
+                inner,
+                _guard: OpenGuard { tracker, host },
+            })
+        })
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// A connection created by [`TrackedConnector`].
+    pub struct TrackedConnection<T> {
+        #[pin]
+        inner: T,
+        _guard: OpenGuard,
+    }
+}
+
+impl<T: Connection> Connection for TrackedConnection<T> {
+    fn connected(&self) -> Connected {
+        self.inner.connected()
+    }
+}
+
+impl<T: AsyncRead> AsyncRead for TrackedConnection<T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        self.project().inner.poll_read(cx, buf)
+    }
+}
+
+impl<T: AsyncWrite> AsyncWrite for TrackedConnection<T> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        self.project().inner.poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.project().inner.poll_shutdown(cx)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[std::io::IoSlice<'_>],
+    ) -> Poll<std::io::Result<usize>> {
+        self.project().inner.poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// Body stream that keeps a request marked as active until it is dropped.
+    pub(crate) struct ActiveBody {
+        #[pin]
+        inner: hyper::Body,
+        _guard: ActiveGuard,
+    }
+}
+
+impl ActiveBody {
+    pub(crate) fn new(inner: hyper::Body, guard: ActiveGuard) -> Self {
+        Self {
+            inner,
+            _guard: guard,
+        }
+    }
+}
+
+impl Stream for ActiveBody {
+    type Item = Result<hyper::body::Bytes, hyper::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.project().inner.poll_next(cx)
+    }
+}
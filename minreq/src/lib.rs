@@ -0,0 +1,248 @@
+use std::{io::Read, str::FromStr};
+
+use anyhttp::{
+    sync::{read_final_response, GenericResponseBody},
+    HttpError, HttpExecutor,
+};
+use http::HeaderValue;
+
+/// Executor for [minreq](https://docs.rs/minreq), a minimal blocking client.
+///
+/// Limitations:
+/// * request and response bodies are buffered in memory
+/// * timeouts are rounded up to whole seconds
+/// * [`anyhttp::RequestPre::connect_timeout`] is ignored, minreq has no
+///   separate connect timeout
+/// * minreq keeps only the last value of repeated response headers
+/// * `Expect` request headers are sent, but the body follows without
+///   waiting for the server. minreq returns interim responses as the final
+///   one, so the executor reads the actual response itself.
+#[derive(Clone, Default)]
+pub struct MinreqExecutor {
+    _private: (),
+}
+
+impl MinreqExecutor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn into_client(self) -> anyhttp::Client<Self> {
+        anyhttp::Client::new(self)
+    }
+}
+
+fn map_method(method: &http::Method) -> minreq::Method {
+    match *method {
+        http::Method::GET => minreq::Method::Get,
+        http::Method::HEAD => minreq::Method::Head,
+        http::Method::POST => minreq::Method::Post,
+        http::Method::PUT => minreq::Method::Put,
+        http::Method::DELETE => minreq::Method::Delete,
+        http::Method::CONNECT => minreq::Method::Connect,
+        http::Method::OPTIONS => minreq::Method::Options,
+        http::Method::TRACE => minreq::Method::Trace,
+        http::Method::PATCH => minreq::Method::Patch,
+        ref other => minreq::Method::Custom(other.to_string()),
+    }
+}
+
+fn map_error(err: minreq::Error) -> HttpError {
+    match &err {
+        minreq::Error::IoError(io) => match io.kind() {
+            std::io::ErrorKind::ConnectionRefused
+            | std::io::ErrorKind::ConnectionReset
+            | std::io::ErrorKind::AddrNotAvailable => HttpError::new_connect(err),
+            std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock => {
+                HttpError::new_timeout(err)
+            }
+            _ => HttpError::new_custom_with_cause("minreq error", err),
+        },
+        minreq::Error::AddressNotFound => HttpError::new_connect(err),
+        // FIXME: more detailed mapping
+        _ => HttpError::new_custom_with_cause("minreq error", err),
+    }
+}
+
+impl HttpExecutor for MinreqExecutor {
+    type RequestBody = anyhttp::RequestBody;
+    type ResponseBody = GenericResponseBody;
+    type Output = Result<anyhttp::Response<GenericResponseBody>, HttpError>;
+
+    fn request_body_from_generic(&self, body: anyhttp::RequestBody) -> Self::RequestBody {
+        body
+    }
+
+    fn new_output_error(&self, error: HttpError) -> Self::Output {
+        Err(error)
+    }
+
+    fn generic_request_body_mut<'a>(
+        &self,
+        body: &'a mut Self::RequestBody,
+    ) -> Option<&'a mut anyhttp::RequestBody> {
+        Some(body)
+    }
+
+    #[cfg(feature = "tracing")]
+    fn instrument_output(
+        &self,
+        output: Self::Output,
+        span: anyhttp::trace::RequestSpan,
+    ) -> Self::Output {
+        span.finish(&output);
+        output
+    }
+
+    fn execute(&self, pre: anyhttp::RequestPre<Self::RequestBody>) -> Self::Output {
+        if pre.cancel.as_ref().is_some_and(|c| c.is_cancelled()) {
+            return Err(HttpError::new_cancelled());
+        }
+        if pre.connect_to.is_some() {
+            return Err(HttpError::new_custom(
+                "connect_to is not supported by the minreq executor",
+            ));
+        }
+        let timeout = pre.remaining_timeout()?;
+        let req = pre.request;
+        let tap = pre.tap;
+        let decompress = pre.decompress;
+        let is_head = req.method == http::Method::HEAD;
+
+        let mut mr = minreq::Request::new(map_method(&req.method), req.uri.to_string());
+        if let Some(timeout) = timeout {
+            let secs = timeout.as_secs() + u64::from(timeout.subsec_nanos() > 0);
+            mr = mr.with_timeout(secs);
+        }
+
+        for (key, value) in &req.headers {
+            let value = value.to_str().map_err(|_err| {
+                HttpError::new_custom(format!(
+                    "could not re-parse request header '{key}': non-utf8 value"
+                ))
+            })?;
+            mr = mr.with_header(key.as_str(), value);
+        }
+
+        match req.body {
+            anyhttp::RequestBody::Empty => {}
+            anyhttp::RequestBody::Bytes(bytes) => mr = mr.with_body(bytes),
+            anyhttp::RequestBody::Read(mut reader) => {
+                // minreq only supports buffered bodies.
+                let mut bytes = Vec::new();
+                reader.read_to_end(&mut bytes).map_err(|err| {
+                    HttpError::new_io(err, Some("could not read request body".to_string()))
+                })?;
+                mr = mr.with_body(bytes);
+            }
+            anyhttp::RequestBody::Stream(stream) => {
+                mr = mr.with_body(stream.read_to_end_blocking()?);
+            }
+        }
+
+        let mut mres = mr.send_lazy().map_err(map_error)?;
+
+        // The final URI is informational, so a URI that `http` can't parse,
+        // like an overlong redirect target, doesn't fail the request.
+        let uri = mres
+            .url
+            .parse::<http::Uri>()
+            .unwrap_or_else(|_| req.uri.clone());
+
+        let status = u16::try_from(mres.status_code)
+            .ok()
+            .and_then(|code| http::StatusCode::from_u16(code).ok())
+            .ok_or_else(|| {
+                HttpError::new_custom(format!("invalid status code {}", mres.status_code))
+            })?;
+        if status.is_informational() && status != http::StatusCode::SWITCHING_PROTOCOLS {
+            // minreq returns interim responses, like `100 Continue` for an
+            // `Expect` header, as the final one, with the rest of the
+            // connection as the body.
+            let res = read_final_response(Box::new(mres), is_head)?;
+            return finish_response(res, uri, decompress, is_head, tap);
+        }
+
+        // The body is read first, since minreq adds the trailers of chunked
+        // bodies to the headers.
+        let mut bytes = Vec::new();
+        let has_body = !is_head && status != 204 && status != 304;
+        if has_body {
+            for byte in &mut mres {
+                let (byte, length) = byte.map_err(map_error)?;
+                bytes.reserve(length);
+                bytes.push(byte);
+            }
+        }
+
+        let mut headers = http::HeaderMap::new();
+        for (key, value) in &mres.headers {
+            let key = http::header::HeaderName::from_str(key)
+                .map_err(|err| HttpError::new_http(err.into()))?;
+            let value = value
+                .parse::<HeaderValue>()
+                .map_err(|err| HttpError::new_http(err.into()))?;
+            headers.append(key, value);
+        }
+
+        let mut extensions = http::Extensions::new();
+        extensions.insert(anyhttp::ReasonPhrase(mres.reason_phrase.clone()));
+
+        // minreq treats a connection closed before `Content-Length` bytes
+        // were received as the end of the body.
+        let expected_len = headers
+            .get(http::header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok()?.parse::<usize>().ok());
+        if has_body && expected_len.is_some_and(|len| bytes.len() < len) {
+            let err = std::io::Error::from(std::io::ErrorKind::UnexpectedEof);
+            return Err(HttpError::new_response_read(
+                Some("connection closed before the end of the body".to_string()),
+                err,
+            ));
+        }
+
+        let res = anyhttp::Response {
+            uri: None,
+            status,
+            version: http::Version::HTTP_11,
+            headers,
+            extensions,
+            body: Box::new(std::io::Cursor::new(bytes)) as Box<dyn Read>,
+        };
+        finish_response(res, uri, decompress, is_head, tap)
+    }
+}
+
+fn finish_response(
+    res: anyhttp::Response<Box<dyn Read>>,
+    uri: http::Uri,
+    decompress: bool,
+    is_head: bool,
+    tap: Option<anyhttp::Tapper>,
+) -> Result<anyhttp::Response<GenericResponseBody>, HttpError> {
+    let (mut res, reader) = res.take_body();
+    res.uri = Some(uri);
+    // minreq returns an empty body for HEAD responses, which can't be
+    // decoded.
+    let reader = if decompress && !is_head {
+        anyhttp::compression::decode_reader(&mut res.headers, reader)
+    } else {
+        reader
+    };
+    if let Some(tap) = tap {
+        anyhttp::run_tap(&tap, &mut res)?;
+    }
+    Ok(res.map_body(|_| GenericResponseBody::Read(reader)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minreq_client() {
+        // `test_sync_executor_strict` is skipped: minreq only supports
+        // timeouts in whole seconds and drops repeated response headers.
+        anyhttp::test::test_sync_executor(MinreqExecutor::new());
+    }
+}
@@ -1,8 +1,14 @@
 use std::{future::Future, pin::Pin, sync::Arc};
 
-use futures::{stream::BoxStream, Stream, TryFutureExt};
+use futures::{stream::BoxStream, Stream, StreamExt, TryFutureExt, TryStreamExt};
+use http::StatusCode;
 
-use crate::{error::HttpError, HttpExecutor, RequestBody, RequestPre, Respond, Response};
+use crate::{
+    body::StreamBody,
+    error::HttpError,
+    retry::{ReplayableRequest, RetryPolicy},
+    HttpExecutor, IntoUri, Method, PoolStats, RequestBody, RequestPre, Respond, Response,
+};
 
 pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
 pub type HttpFuture<'a, T> = BoxFuture<'a, Result<T, HttpError>>;
@@ -32,6 +38,13 @@ where
         self.into_chunks()
     }
 
+    #[cfg(feature = "bytes")]
+    fn into_bytes_chunks_boxed(
+        self: Box<Self>,
+    ) -> BoxStream<'static, Result<bytes::Bytes, HttpError>> {
+        Box::new(self.0).into_bytes_chunks_boxed()
+    }
+
     fn bytes(self) -> Self::BytesOutput {
         Box::pin(self.0.bytes())
     }
@@ -47,6 +60,19 @@ where
     fn reader_boxed(self: Box<Self>) -> Self::Reader {
         self.reader()
     }
+
+    #[cfg(feature = "bytes")]
+    fn bytes_buf_boxed(self: Box<Self>) -> HttpFuture<'static, bytes::Bytes> {
+        Box::new(self.0).bytes_buf_boxed()
+    }
+
+    fn drain_async_boxed(self: Box<Self>) -> HttpFuture<'static, u64> {
+        Box::new(self.0).drain_async_boxed()
+    }
+
+    fn trailers_boxed(self: Box<Self>) -> HttpFuture<'static, Option<http::HeaderMap>> {
+        Box::new(self.0).trailers_boxed()
+    }
 }
 
 struct DynWrapper<E>(E);
@@ -116,6 +142,26 @@ where
         self.execute(pre)
     }
 
+    fn generic_request_body_mut<'a>(
+        &self,
+        body: &'a mut RequestBody,
+    ) -> Option<&'a mut RequestBody> {
+        Some(body)
+    }
+
+    fn pool_stats(&self) -> Option<PoolStats> {
+        self.0.pool_stats()
+    }
+
+    #[cfg(feature = "tracing")]
+    fn instrument_output(
+        &self,
+        output: Self::Output,
+        span: crate::trace::RequestSpan,
+    ) -> Self::Output {
+        Box::pin(span.instrument(output))
+    }
+
     fn new_output_error(&self, error: HttpError) -> Self::Output {
         Box::pin(std::future::ready(Err(error)))
     }
@@ -147,27 +193,513 @@ where
     }
 }
 
+impl<E> super::Client<E>
+where
+    E: HttpExecutor,
+    E::ResponseBody: Respond,
+    <E::ResponseBody as Respond>::Chunks: Stream<Item = Result<Vec<u8>, HttpError>>,
+    E::Output: Future<Output = Result<Response<E::ResponseBody>, HttpError>>,
+{
+    /// Send a request and only return the response status.
+    ///
+    /// The body is drained to allow connection re-use.
+    /// Useful for health checks.
+    pub async fn status_async<M, U>(&self, method: M, uri: U) -> Result<StatusCode, HttpError>
+    where
+        Method: TryFrom<M>,
+        <Method as TryFrom<M>>::Error: Into<http::Error>,
+        U: IntoUri,
+    {
+        let (res, body) = self.request(method, uri).send().await?.take_body();
+
+        let mut chunks = Box::pin(body.into_chunks());
+        while let Some(chunk) = chunks.next().await {
+            chunk?;
+        }
+
+        Ok(res.status)
+    }
+
+    /// Send many requests, with at most `concurrency` of them in flight.
+    ///
+    /// Requests can be built with [`crate::RequestBuilder::build`].
+    /// Results are yielded as the requests complete, not in the order of
+    /// the input. A failed request doesn't stop the others.
+    pub fn send_all<'a, I>(
+        &'a self,
+        requests: I,
+        concurrency: usize,
+    ) -> impl Stream<Item = Result<Response<E::ResponseBody>, HttpError>> + 'a
+    where
+        I: IntoIterator,
+        I::IntoIter: 'a,
+        I::Item: Into<RequestPre<E::RequestBody>>,
+    {
+        futures::stream::iter(requests)
+            .map(move |request| self.send_pre(request.into()))
+            // A limit of 0 would never make progress.
+            .buffer_unordered(concurrency.max(1))
+    }
+}
+
 impl<B> Response<B>
 where
     B: Respond,
     <B as Respond>::BytesOutput: Future<Output = Result<Vec<u8>, HttpError>> + Send + 'static,
-    <B as Respond>::Chunks: Future<Output = Result<Vec<u8>, HttpError>> + Send + 'static,
+    <B as Respond>::Chunks: Stream<Item = Result<Vec<u8>, HttpError>> + Send + 'static,
 {
     pub async fn bytes_async(self) -> Result<Vec<u8>, HttpError> {
         self.body.bytes().await
     }
 
+    /// Read the whole body as [`bytes::Bytes`], for use with `bytes`-based
+    /// libraries.
+    ///
+    /// Avoids copying the body with backends that support it, see
+    /// [`Respond::bytes_buf_boxed`]. Otherwise this is as fast as
+    /// [`Self::bytes_async`].
+    #[cfg(feature = "bytes")]
+    pub async fn bytes_buf_async(self) -> Result<bytes::Bytes, HttpError> {
+        Box::new(self.body).bytes_buf_boxed().await
+    }
+
+    /// Stream the body as [`bytes::Bytes`] chunks.
+    ///
+    /// Like [`Respond::into_chunks`], but avoids copying each chunk with
+    /// backends that support it, see [`Respond::into_bytes_chunks_boxed`].
+    #[cfg(feature = "bytes")]
+    pub fn into_bytes_chunks(
+        self,
+    ) -> impl Stream<Item = Result<bytes::Bytes, HttpError>> + Send + 'static {
+        Box::new(self.body).into_bytes_chunks_boxed()
+    }
+
+    /// Read and discard the whole body, returning the number of bytes read.
+    ///
+    /// For callers that only need the status or headers: reading the body
+    /// to the end allows backends to reuse the connection.
+    pub async fn drain_async(self) -> Result<u64, HttpError> {
+        Box::new(self.body).drain_async_boxed().await
+    }
+
+    /// Read the whole body into a new response, so the status, headers and
+    /// extensions stay accessible after the body was read, and the body can
+    /// be inspected multiple times.
+    pub async fn buffered_async(self) -> Result<Response<Vec<u8>>, HttpError> {
+        let (res, body) = self.take_body();
+        let bytes = body.bytes().await?;
+        Ok(res.map_body(|_| bytes))
+    }
+
+    /// Read the body and decode it according to the `Content-Encoding`
+    /// header, for responses that were not decompressed by the executor.
+    ///
+    /// Bodies with an unsupported encoding are returned as-is. Only the
+    /// encodings enabled by the `compression` feature are decoded.
+    pub async fn bytes_decoded_async(self) -> Result<Vec<u8>, HttpError> {
+        let (mut res, body) = self.take_body();
+        let bytes = body.bytes().await?;
+        match crate::compression::Encoding::take_from_headers(&mut res.headers) {
+            Some(encoding) => {
+                let chunks = futures::stream::once(async move { Ok(bytes) }).boxed();
+                encoding.decode_stream(chunks).try_concat().await
+            }
+            None => Ok(bytes),
+        }
+    }
+
+    /// Like [`Response::error_for_status`], but on failure reads up to
+    /// `limit` bytes of the body and includes them in the error message.
+    ///
+    /// The body is only consumed if the status is not a success.
+    pub async fn error_for_status_with_body_async(self, limit: usize) -> Result<Self, HttpError> {
+        if self.status.is_success() {
+            return Ok(self);
+        }
+        let (res, body) = self.take_body();
+        let mut chunks = Box::pin(body.into_chunks());
+        let mut snippet = Vec::new();
+        // The status error is more useful than a read error, so the body
+        // is best-effort.
+        while let Some(Ok(chunk)) = chunks.next().await {
+            snippet.extend(chunk);
+            if snippet.len() >= limit {
+                break;
+            }
+        }
+        snippet.truncate(limit);
+        Err(res.status_error_with_body(&snippet))
+    }
+
+    /// Read and discard the rest of the body, then return the trailing
+    /// headers sent after it, for example the status of a gRPC-web call.
+    ///
+    /// Trailers are only available after the body was consumed, so the
+    /// body can't be read afterwards. Returns `None` if the response has
+    /// no trailers, or if the backend doesn't support them, see
+    /// [`Respond::trailers_boxed`].
+    pub async fn trailers_async(self) -> Result<Option<http::HeaderMap>, HttpError> {
+        Box::new(self.body).trailers_boxed().await
+    }
+
+    /// Stream the body chunks, like `res.body.into_chunks()`.
+    pub fn chunks_async(self) -> DynChunksStream {
+        Box::pin(self.body.into_chunks())
+    }
+
+    /// Stream the body chunks together with the total number of bytes read
+    /// so far, for example to report download progress against
+    /// [`Response::content_length`].
+    pub fn into_chunks_with_progress(
+        self,
+    ) -> impl Stream<Item = Result<(Vec<u8>, u64), HttpError>> + Send + 'static {
+        let mut total = 0u64;
+        self.body.into_chunks().map_ok(move |chunk| {
+            total += chunk.len() as u64;
+            (chunk, total)
+        })
+    }
+
+    /// Read the body through a buffered reader, for example to parse
+    /// framing with [`futures::AsyncBufReadExt::read_line`].
+    pub fn into_async_buf_read(self) -> futures::io::BufReader<DynReader>
+    where
+        B::Reader: futures::io::AsyncRead + Send + 'static,
+    {
+        futures::io::BufReader::new(Box::pin(self.body.reader()))
+    }
+
+    /// Stream the body as text lines.
+    ///
+    /// Lines are split on `\n`, with a trailing `\r` removed.
+    /// Trailing data without a final newline is returned as the last line.
+    pub fn lines_async(self) -> impl Stream<Item = Result<String, HttpError>> + Send + 'static {
+        let chunks = Box::pin(self.body.into_chunks());
+        futures::stream::try_unfold(
+            (chunks, Vec::new(), false),
+            |(mut chunks, mut buffer, mut done)| async move {
+                loop {
+                    if let Some(pos) = buffer.iter().position(|b| *b == b'\n') {
+                        let rest = buffer.split_off(pos + 1);
+                        let line = std::mem::replace(&mut buffer, rest);
+                        return Ok(Some((decode_line(line)?, (chunks, buffer, done))));
+                    }
+                    if done {
+                        if buffer.is_empty() {
+                            return Ok(None);
+                        }
+                        let line = std::mem::take(&mut buffer);
+                        return Ok(Some((decode_line(line)?, (chunks, buffer, done))));
+                    }
+                    match chunks.next().await {
+                        Some(chunk) => buffer.extend(chunk?),
+                        None => done = true,
+                    }
+                }
+            },
+        )
+    }
+
+    /// Deserialize a JSON array or newline-delimited JSON incrementally,
+    /// yielding one value at a time.
+    ///
+    /// Only the current value is buffered, so large list responses can be
+    /// processed without reading the whole body into memory.
+    /// A body that starts with `[` is read as a single array.
+    #[cfg(feature = "json")]
+    pub fn json_stream_async<T>(self) -> impl Stream<Item = Result<T, HttpError>> + Send + 'static
+    where
+        T: serde::de::DeserializeOwned + Send + 'static,
+    {
+        let chunks = Box::pin(self.body.into_chunks());
+        futures::stream::try_unfold(
+            (chunks, JsonFramer::default(), false),
+            |(mut chunks, mut framer, mut done)| async move {
+                loop {
+                    if let Some(value) = framer.next_value(done)? {
+                        return Ok(Some((value, (chunks, framer, done))));
+                    }
+                    if done {
+                        return Ok(None);
+                    }
+                    match chunks.next().await {
+                        Some(chunk) => framer.buffer.extend(chunk?),
+                        None => done = true,
+                    }
+                }
+            },
+        )
+    }
+
     #[cfg(feature = "json")]
     pub async fn json_async<T: serde::de::DeserializeOwned>(self) -> Result<T, HttpError> {
         let bytes = self.bytes_async().await?;
-        serde_json::from_slice(&bytes).map_err(|err| {
-            HttpError::new(
-                crate::error::Kind::InvalidResponseJson,
-                Some(Box::new(err)),
-                None,
-            )
-        })
+        serde_json::from_slice(&bytes).map_err(|err| crate::types::json_body_error(err, &bytes))
+    }
+
+    /// Like [`Self::json_async`], but first check that the `Content-Type`
+    /// is a JSON media type.
+    ///
+    /// Fails with an [`HttpError::is_unexpected_content_type`] error
+    /// otherwise, for example for an HTML error page, instead of a
+    /// confusing parse error. The body is not read in that case.
+    #[cfg(feature = "json")]
+    pub async fn json_if_json_async<T: serde::de::DeserializeOwned>(self) -> Result<T, HttpError> {
+        self.expect_json_content_type()?;
+        self.json_async().await
+    }
+
+    /// Read the body and pass it to `f`, for deserializing into types that
+    /// borrow from the body, like `&str` fields, without copying.
+    ///
+    /// Errors returned by `f` are reported like invalid JSON in
+    /// [`Self::json_async`].
+    #[cfg(feature = "json")]
+    pub async fn with_json_bytes_async<T, F>(self, f: F) -> Result<T, HttpError>
+    where
+        F: FnOnce(&[u8]) -> Result<T, serde_json::Error>,
+    {
+        let bytes = self.bytes_async().await?;
+        f(&bytes).map_err(|err| crate::types::json_body_error(err, &bytes))
+    }
+
+    /// Deserialize a JSON body that starts with a fixed prefix.
+    ///
+    /// Some APIs prefix JSON with `)]}',\n` to prevent JSON hijacking.
+    /// Returns an error if the prefix is missing.
+    #[cfg(feature = "json")]
+    pub async fn json_with_prefix_strip_async<T: serde::de::DeserializeOwned>(
+        self,
+        prefix: &str,
+    ) -> Result<T, HttpError> {
+        let bytes = self.bytes_async().await?;
+        let json = crate::types::strip_json_prefix(&bytes, prefix)?;
+        serde_json::from_slice(json).map_err(|err| crate::types::json_body_error(err, json))
+    }
+}
+
+/// Box the response body as a chunk stream, reading the first chunk if
+/// `peek` is set.
+async fn finish_attempt<B>(
+    result: Result<Response<B>, HttpError>,
+    peek: bool,
+) -> Result<Response<DynResponseBody>, HttpError>
+where
+    B: Respond,
+    <B as Respond>::Chunks: Stream<Item = Result<Vec<u8>, HttpError>> + Send + 'static,
+{
+    let (res, body) = result?.take_body();
+    let mut chunks: DynChunksStream = Box::pin(body.into_chunks());
+    if peek {
+        if let Some(first) = chunks.next().await.transpose()? {
+            chunks = futures::stream::once(async move { Ok(first) })
+                .chain(chunks)
+                .boxed();
+        }
+    }
+    Ok(res.map_body(|_| -> DynResponseBody { Box::new(StreamBody::from(chunks)) }))
+}
+
+fn decode_line(mut line: Vec<u8>) -> Result<String, HttpError> {
+    if line.last() == Some(&b'\n') {
+        line.pop();
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+    }
+    String::from_utf8(line)
+        .map_err(|err| HttpError::new_response_read(Some("invalid utf8 in line".to_string()), err))
+}
+
+/// Splits a JSON array or a sequence of JSON values into single values.
+#[cfg(feature = "json")]
+#[derive(Default)]
+struct JsonFramer {
+    buffer: Vec<u8>,
+    /// Whether the body is an array, `None` until the first value starts.
+    array: Option<bool>,
+    /// Set when the closing bracket of the array was read.
+    finished: bool,
+}
+
+#[cfg(feature = "json")]
+impl JsonFramer {
+    fn skip_whitespace(&mut self) {
+        let count = self
+            .buffer
+            .iter()
+            .take_while(|b| b.is_ascii_whitespace())
+            .count();
+        self.buffer.drain(..count);
+    }
+
+    fn invalid(message: &str) -> HttpError {
+        HttpError::new(
+            crate::error::Kind::InvalidResponseJson,
+            None,
+            Some(message.to_string()),
+        )
+    }
+
+    /// Parse the next value from the buffer.
+    ///
+    /// Returns `None` if more data is needed, or at the end of the body if
+    /// `eof` is set.
+    fn next_value<T: serde::de::DeserializeOwned>(
+        &mut self,
+        eof: bool,
+    ) -> Result<Option<T>, HttpError> {
+        self.skip_whitespace();
+        if self.finished {
+            if !self.buffer.is_empty() {
+                return Err(Self::invalid("trailing data after JSON array"));
+            }
+            return Ok(None);
+        }
+        let Some(&first) = self.buffer.first() else {
+            if eof && self.array == Some(true) {
+                return Err(Self::invalid("unterminated JSON array"));
+            }
+            return Ok(None);
+        };
+        let array = *self.array.get_or_insert_with(|| {
+            let array = first == b'[';
+            if array {
+                self.buffer.remove(0);
+            }
+            array
+        });
+        self.skip_whitespace();
+        if array && self.buffer.first() == Some(&b']') {
+            self.buffer.remove(0);
+            self.finished = true;
+            return self.next_value(eof);
+        }
+
+        let mut values = serde_json::Deserializer::from_slice(&self.buffer).into_iter::<T>();
+        let value = match values.next() {
+            Some(Ok(value)) => value,
+            Some(Err(err)) if err.is_eof() && !eof => return Ok(None),
+            Some(Err(err)) => {
+                return Err(HttpError::new(
+                    crate::error::Kind::InvalidResponseJson,
+                    Some(Box::new(err)),
+                    None,
+                ))
+            }
+            None => return Ok(None),
+        };
+        let end = values.byte_offset();
+        let next = end
+            + self.buffer[end..]
+                .iter()
+                .take_while(|b| b.is_ascii_whitespace())
+                .count();
+        if array {
+            match self.buffer.get(next) {
+                Some(b',') => {
+                    self.buffer.drain(..=next);
+                }
+                Some(b']') => {
+                    self.buffer.drain(..next);
+                }
+                Some(_) => return Err(Self::invalid("expected ',' or ']' in JSON array")),
+                None if eof => return Err(Self::invalid("unterminated JSON array")),
+                // The separator is not there yet.
+                None => return Ok(None),
+            }
+        } else {
+            // A number at the end of the buffer may continue in the next
+            // chunk.
+            if end == self.buffer.len() && !eof {
+                return Ok(None);
+            }
+            self.buffer.drain(..end);
+        }
+        Ok(Some(value))
     }
 }
 
 pub type DynClient = super::Client<DynExecutor>;
+
+/// Executor wrapper that retries failed requests.
+///
+/// See [`crate::retry`].
+pub struct RetryExecutor<E> {
+    exec: Arc<E>,
+    policy: RetryPolicy,
+}
+
+impl<E> RetryExecutor<E> {
+    pub fn new(exec: E, policy: RetryPolicy) -> Self {
+        Self {
+            exec: Arc::new(exec),
+            policy,
+        }
+    }
+}
+
+impl<E> HttpExecutor for RetryExecutor<E>
+where
+    E: HttpExecutor + Send + Sync + 'static,
+    E::ResponseBody: Respond + Send + 'static,
+    <E::ResponseBody as Respond>::Chunks:
+        Stream<Item = Result<Vec<u8>, HttpError>> + Send + 'static,
+    E::Output: Future<Output = Result<Response<E::ResponseBody>, HttpError>> + Send + 'static,
+{
+    type RequestBody = RequestBody;
+    type ResponseBody = DynResponseBody;
+    type Output = HttpFuture<'static, Response<DynResponseBody>>;
+
+    fn request_body_from_generic(&self, body: RequestBody) -> Self::RequestBody {
+        body
+    }
+
+    fn new_output_error(&self, error: HttpError) -> Self::Output {
+        Box::pin(std::future::ready(Err(error)))
+    }
+
+    fn execute(&self, pre: RequestPre<Self::RequestBody>) -> Self::Output {
+        self.policy.on_request();
+        // The first attempt is started right away, because streaming bodies
+        // are not `Send`.
+        let replay = ReplayableRequest::new(&pre);
+        let peek = replay.is_some() && self.policy.peek_body(&pre.request.method);
+        let first = self.exec.execute_generic(pre);
+        let exec = self.exec.clone();
+        let policy = self.policy.clone();
+
+        Box::pin(async move {
+            let mut result = finish_attempt(first.await, peek).await;
+            if let Some(replay) = replay {
+                let mut retries = 0;
+                while policy.should_retry(retries, replay.method(), &result) {
+                    retries += 1;
+                    let res = exec.execute_generic(replay.to_pre()).await;
+                    result = finish_attempt(res, peek).await;
+                }
+            }
+            result
+        })
+    }
+
+    fn generic_request_body_mut<'a>(
+        &self,
+        body: &'a mut RequestBody,
+    ) -> Option<&'a mut RequestBody> {
+        Some(body)
+    }
+
+    fn pool_stats(&self) -> Option<PoolStats> {
+        self.exec.pool_stats()
+    }
+
+    #[cfg(feature = "tracing")]
+    fn instrument_output(
+        &self,
+        output: Self::Output,
+        span: crate::trace::RequestSpan,
+    ) -> Self::Output {
+        Box::pin(span.instrument(output))
+    }
+}
@@ -0,0 +1,60 @@
+//! Building blocks for executor response bodies.
+//!
+//! Backends that can provide the body as a stream of chunks can use
+//! [`StreamBody`] as their [`crate::HttpExecutor::ResponseBody`] instead of
+//! implementing [`Respond`] themselves.
+
+use futures::{Stream, StreamExt, TryStreamExt};
+
+use crate::{DynChunksStream, DynReader, HttpError, HttpFuture, Respond};
+
+/// A response body backed by a stream of chunks.
+///
+/// [`Respond::bytes`] concatenates the chunks, and [`Respond::reader`]
+/// reads from the stream.
+pub struct StreamBody(DynChunksStream);
+
+impl StreamBody {
+    pub fn new<S>(chunks: S) -> Self
+    where
+        S: Stream<Item = Result<Vec<u8>, HttpError>> + Send + 'static,
+    {
+        Self(chunks.boxed())
+    }
+}
+
+impl From<DynChunksStream> for StreamBody {
+    fn from(chunks: DynChunksStream) -> Self {
+        Self(chunks)
+    }
+}
+
+impl Respond for StreamBody {
+    type Chunks = DynChunksStream;
+    type BytesOutput = HttpFuture<'static, Vec<u8>>;
+    type Reader = DynReader;
+
+    fn into_chunks(self) -> Self::Chunks {
+        self.0
+    }
+
+    fn into_chunks_boxed(self: Box<Self>) -> Self::Chunks {
+        self.0
+    }
+
+    fn bytes(self) -> Self::BytesOutput {
+        Box::pin(self.0.try_concat())
+    }
+
+    fn bytes_boxed(self: Box<Self>) -> Self::BytesOutput {
+        (*self).bytes()
+    }
+
+    fn reader(self) -> Self::Reader {
+        Box::pin(self.0.map_err(std::io::Error::other).into_async_read())
+    }
+
+    fn reader_boxed(self: Box<Self>) -> Self::Reader {
+        (*self).reader()
+    }
+}
@@ -1,6 +1,17 @@
 use http::{header::HeaderName, HeaderValue, Method, Uri};
 
-use crate::{Client, HttpError, HttpExecutor, Request, RequestBody, RequestPre};
+use crate::{
+    cancel::CancelToken, Client, ConnectTo, HeaderOrder, HttpError, HttpExecutor, IntoUri, Request,
+    RequestBody, RequestPre,
+};
+
+/// Characters that are percent-encoded in a path segment: everything except
+/// the unreserved characters of RFC 3986.
+const PATH_SEGMENT: &percent_encoding::AsciiSet = &percent_encoding::NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
 
 pub struct RequestBuilder<E: HttpExecutor> {
     client: Client<E>,
@@ -10,21 +21,21 @@ pub struct RequestBuilder<E: HttpExecutor> {
 impl<E: HttpExecutor + Sized> RequestBuilder<E> {
     pub fn new(client: Client<E>) -> Self {
         let body = client.0.exec.request_body_from_generic(RequestBody::Empty);
-        let pre = RequestPre {
-            request: Request::new(body),
-            timeout: None,
-            tap: None,
-        };
         Self {
             client,
-            result: Ok(pre),
+            result: Ok(RequestPre::new(Request::new(body))),
         }
     }
 
+    /// Set the HTTP version of the request.
+    ///
+    /// Which versions are supported depends on the executor: the hyper
+    /// executor fails `HTTP/2` requests that can't be sent over HTTP/2, while
+    /// the other executors only speak HTTP/1.1 and ignore the version.
     pub fn version(mut self, version: http::Version) -> Self {
-        self.result = self.result.and_then(|mut pre| {
+        self.result = self.result.map(|mut pre| {
             pre.request.version = version;
-            Ok(pre)
+            pre
         });
         self
     }
@@ -43,24 +54,83 @@ impl<E: HttpExecutor + Sized> RequestBuilder<E> {
         self
     }
 
-    pub fn uri<T>(mut self, uri: T) -> Self
-    where
-        http::Uri: TryFrom<T>,
-        <http::Uri as TryFrom<T>>::Error: Into<http::Error>,
-    {
+    /// Set the URI, from anything implementing [`IntoUri`] like a `&str`
+    /// or a [`url::Url`].
+    pub fn uri(mut self, uri: impl IntoUri) -> Self {
         self.result = self.result.and_then(move |mut r| {
-            let uri = Uri::try_from(uri)
-                .map_err(|err| HttpError::new_invalid_request(err.into(), None))?;
+            let uri = uri
+                .into_uri()
+                .map_err(|err| HttpError::new_invalid_request(err, None))?;
             r.request.uri = uri;
             Ok(r)
         });
         self
     }
 
+    /// Set the URI from a [`url::Url`].
+    ///
+    /// Same as [`Self::uri`]. The fragment is not sent and is dropped.
+    #[cfg(feature = "url")]
+    pub fn url(self, url: &url::Url) -> Self {
+        self.uri(url)
+    }
+
+    /// Append a single path segment to the URI, percent-encoding it.
+    ///
+    /// Slashes and other special characters in `segment` are encoded, so
+    /// user input can't change the structure of the path. The query is kept.
+    /// The dot segments `.` and `..` are rejected.
+    pub fn path_segment(mut self, segment: &str) -> Self {
+        self.result = self.result.and_then(move |mut r| {
+            if segment == "." || segment == ".." {
+                return Err(HttpError::new(
+                    crate::error::Kind::InvalidRequest,
+                    None,
+                    Some(format!("invalid path segment '{segment}'")),
+                ));
+            }
+            let mut parts = std::mem::take(&mut r.request.uri).into_parts();
+            let (path, query) = match &parts.path_and_query {
+                Some(pq) => (pq.path(), pq.query()),
+                None => ("/", None),
+            };
+            let mut path_and_query = path.to_string();
+            if !path_and_query.ends_with('/') {
+                path_and_query.push('/');
+            }
+            path_and_query.extend(percent_encoding::utf8_percent_encode(segment, PATH_SEGMENT));
+            if let Some(query) = query {
+                path_and_query.push('?');
+                path_and_query.push_str(query);
+            }
+            parts.path_and_query = Some(
+                path_and_query
+                    .parse()
+                    .map_err(|err| HttpError::new_invalid_request(err, None))?,
+            );
+            r.request.uri =
+                Uri::from_parts(parts).map_err(|err| HttpError::new_invalid_request(err, None))?;
+            Ok(r)
+        });
+        self
+    }
+
     pub fn uri_mut(&mut self) -> Option<&mut Uri> {
         self.result.as_mut().ok().map(|p| &mut p.request.uri)
     }
 
+    pub fn method_mut(&mut self) -> Option<&mut Method> {
+        self.result.as_mut().ok().map(|p| &mut p.request.method)
+    }
+
+    pub fn version_mut(&mut self) -> Option<&mut http::Version> {
+        self.result.as_mut().ok().map(|p| &mut p.request.version)
+    }
+
+    pub fn headers_mut(&mut self) -> Option<&mut http::HeaderMap> {
+        self.result.as_mut().ok().map(|p| &mut p.request.headers)
+    }
+
     pub fn header_sensitive<K, V>(mut self, key: K, value: V, is_sensitive: bool) -> Self
     where
         HeaderName: TryFrom<K>,
@@ -92,9 +162,51 @@ impl<E: HttpExecutor + Sized> RequestBuilder<E> {
     }
 
     pub fn headers(mut self, headers: http::HeaderMap) -> Self {
-        self.result = self.result.and_then(|mut pre| {
+        self.result = self.result.map(|mut pre| {
             pre.request.headers.extend(headers);
-            Ok(pre)
+            pre
+        });
+        self
+    }
+
+    /// Add a request extension, replacing any previous value of the same type.
+    ///
+    /// Extensions are visible to middleware and are forwarded to backends
+    /// that use `http::Request`, like hyper, where connectors can read them.
+    pub fn extension<T: Send + Sync + 'static>(mut self, value: T) -> Self {
+        self.result = self.result.map(|mut pre| {
+            pre.request.extensions.insert(value);
+            pre
+        });
+        self
+    }
+
+    /// Send `Expect: 100-continue`, asking the server to confirm that it
+    /// accepts the request before the body is uploaded.
+    ///
+    /// Only curl waits for the interim `100 Continue` response before the
+    /// body is uploaded. hyper, ureq and minreq send the header and the body
+    /// right away and skip the interim response, so the server can still
+    /// reject the request before reading the whole body.
+    ///
+    /// surf drops the header, since async-h1 would return the interim
+    /// response as the final one.
+    pub fn expect_continue(self) -> Self {
+        self.header(http::header::EXPECT, "100-continue")
+    }
+
+    /// Set the order in which headers are written to the wire.
+    ///
+    /// Listed headers are sent first, in the given order, followed by all
+    /// other headers.
+    ///
+    /// This is best-effort and backend-dependent: backends may add their own
+    /// headers (like `host` or `content-length`) or not preserve the order.
+    /// The hyper backend writes headers in the given order.
+    pub fn header_order(mut self, order: Vec<HeaderName>) -> Self {
+        self.result = self.result.map(|mut pre| {
+            pre.request.extensions.insert(HeaderOrder(order));
+            pre
         });
         self
     }
@@ -105,20 +217,39 @@ impl<E: HttpExecutor + Sized> RequestBuilder<E> {
         U: std::fmt::Display,
         P: std::fmt::Display,
     {
-        use std::io::Write;
-
-        let mut header_value = b"Basic ".to_vec();
-        {
-            let mut encoder =
-                base64::write::EncoderWriter::new(&mut header_value, base64::STANDARD);
-            // The unwraps here are fine because Vec::write* is infallible.
-            write!(encoder, "{}:", username).unwrap();
-            if let Some(password) = password {
-                write!(encoder, "{}", password).unwrap();
+        self.header_sensitive(
+            http::header::AUTHORIZATION,
+            basic_auth_value(username, password),
+            true,
+        )
+    }
+
+    /// Add a cookie to the request.
+    ///
+    /// Cookies are merged into a single `Cookie` header.
+    /// A `Cookie` header set on the builder takes precedence over cookies
+    /// from the client cookie jar.
+    pub fn cookie<N, V>(mut self, name: N, value: V) -> Self
+    where
+        N: std::fmt::Display,
+        V: std::fmt::Display,
+    {
+        self.result = self.result.and_then(move |mut pre| {
+            let mut cookies = Vec::new();
+            for existing in pre.request.headers.get_all(http::header::COOKIE) {
+                let existing = existing
+                    .to_str()
+                    .map_err(|err| HttpError::new_invalid_request(err, None))?;
+                cookies.push(existing.to_string());
             }
-        }
+            cookies.push(format!("{name}={value}"));
 
-        self.header_sensitive(http::header::AUTHORIZATION, header_value, true)
+            let value = HeaderValue::try_from(cookies.join("; "))
+                .map_err(|err| HttpError::new_invalid_request(err, None))?;
+            pre.request.headers.insert(http::header::COOKIE, value);
+            Ok(pre)
+        });
+        self
     }
 
     pub fn bearer_auth<T>(self, token: T) -> Self
@@ -143,6 +274,51 @@ impl<E: HttpExecutor + Sized> RequestBuilder<E> {
         self
     }
 
+    /// Stream the body from an async reader, for example a file.
+    ///
+    /// Sync executors buffer the whole body before sending it.
+    /// See [`RequestBody::Stream`].
+    #[cfg(feature = "async")]
+    pub fn body_async_read<R>(mut self, reader: R) -> Self
+    where
+        R: futures::io::AsyncRead + Send + 'static,
+    {
+        let client = &self.client;
+        self.result = self.result.map(|mut pre| {
+            let body = RequestBody::Stream(crate::BodyStream::from_async_read(reader));
+            pre.request.body = client.0.exec.request_body_from_generic(body);
+            pre
+        });
+        self
+    }
+
+    /// Stream the body from a channel.
+    ///
+    /// Chunks pushed through the returned sender are sent while the request
+    /// is in flight, which allows long-lived uploads like log shipping. The
+    /// body ends when the sender and all its clones are dropped, so the
+    /// request only completes after that.
+    ///
+    /// The channel buffers up to 16 chunks, see [`crate::BodyStream::channel`].
+    /// Sync executors buffer the whole body before sending it.
+    #[cfg(feature = "async")]
+    pub fn body_channel(mut self) -> (futures::channel::mpsc::Sender<Vec<u8>>, Self) {
+        let (sender, stream) = crate::BodyStream::channel(16);
+        let client = &self.client;
+        self.result = self.result.map(|mut pre| {
+            pre.request.body = client
+                .0
+                .exec
+                .request_body_from_generic(RequestBody::Stream(stream));
+            pre
+        });
+        (sender, self)
+    }
+
+    /// Serialize the body as JSON.
+    ///
+    /// Sets `Content-Type: application/json`, unless a content type was
+    /// already set, for example `application/vnd.api+json`.
     #[cfg(feature = "json")]
     pub fn json<T: serde::Serialize + ?Sized>(mut self, value: &T) -> Self {
         let client = &self.client;
@@ -161,15 +337,24 @@ impl<E: HttpExecutor + Sized> RequestBuilder<E> {
 
             r.request.body = body;
 
-            r.request.headers.insert(
-                http::header::CONTENT_TYPE,
-                HeaderValue::from_static("application/json"),
-            );
+            r.request
+                .headers
+                .entry(http::header::CONTENT_TYPE)
+                .or_insert(HeaderValue::from_static("application/json"));
             Ok(r)
         });
         self
     }
 
+    /// Like [`Self::json`], for a body assembled at runtime.
+    #[cfg(feature = "json")]
+    pub fn json_value(self, value: serde_json::Value) -> Self {
+        self.json(&value)
+    }
+
+    /// Serialize the body as `application/x-www-form-urlencoded`.
+    ///
+    /// Like [`Self::json`], an existing `Content-Type` header is kept.
     #[cfg(feature = "urlencoding")]
     pub fn form<T: serde::Serialize>(mut self, data: &T) -> Self {
         self.result = self.result.and_then(|mut pre| {
@@ -188,26 +373,196 @@ impl<E: HttpExecutor + Sized> RequestBuilder<E> {
                     )
                 })?;
             pre.request.body = body;
+            pre.request
+                .headers
+                .entry(http::header::CONTENT_TYPE)
+                .or_insert(HeaderValue::from_static(
+                    "application/x-www-form-urlencoded",
+                ));
+
+            Ok(pre)
+        });
+        self
+    }
+
+    /// Like [`Self::form`], but for form data that is only known at runtime.
+    ///
+    /// Pairs are percent-encoded in the given order, and keys may repeat.
+    #[cfg(feature = "urlencoding")]
+    pub fn form_urlencoded_pairs<I, K, V>(self, pairs: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let pairs = pairs
+            .into_iter()
+            .map(|(key, value)| (key.as_ref().to_string(), value.as_ref().to_string()))
+            .collect::<Vec<_>>();
+        self.form(&pairs)
+    }
+
+    /// Compress the body with gzip and set `Content-Encoding: gzip`.
+    ///
+    /// Must be called after the body is set.
+    /// Only the server needs to support compressed request bodies.
+    ///
+    /// Fails with an invalid request error if the executor uses its own body
+    /// type (see [`HttpExecutor::generic_request_body_mut`]).
+    #[cfg(feature = "compression")]
+    pub fn gzip_body(mut self) -> Self {
+        let client = &self.client;
+        self.result = self.result.and_then(|mut pre| {
+            let body = client
+                .0
+                .exec
+                .generic_request_body_mut(&mut pre.request.body)
+                .ok_or_else(|| {
+                    HttpError::new(
+                        crate::error::Kind::InvalidRequest,
+                        None,
+                        Some("request body of this executor can not be compressed".to_string()),
+                    )
+                })?;
+            if matches!(body, RequestBody::Empty) {
+                return Ok(pre);
+            }
+
+            let raw = std::mem::replace(body, RequestBody::Empty);
+            *body = crate::compression::gzip_request_body(raw);
+            pre.request.headers.remove(http::header::CONTENT_LENGTH);
             pre.request.headers.insert(
-                http::header::CONTENT_TYPE,
-                HeaderValue::from_static("application/x-www-form-urlencoded"),
+                http::header::CONTENT_ENCODING,
+                HeaderValue::from_static("gzip"),
             );
-
             Ok(pre)
         });
         self
     }
 
     pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
-        self.result = self.result.and_then(move |mut pre| {
+        self.result = self.result.map(move |mut pre| {
             pre.timeout = Some(timeout);
-            Ok(pre)
+            pre
+        });
+        self
+    }
+
+    /// Set an absolute deadline for the request, for example to share one
+    /// time budget between several requests.
+    ///
+    /// Executors compute the remaining time when the request is sent.
+    /// See [`RequestPre::deadline`].
+    pub fn deadline(mut self, deadline: std::time::Instant) -> Self {
+        self.result = self.result.map(move |mut pre| {
+            pre.deadline = Some(deadline);
+            pre
+        });
+        self
+    }
+
+    /// Set a timeout for establishing the connection.
+    ///
+    /// Useful in combination with a long [`Self::timeout`] for streaming
+    /// responses. See [`RequestPre::connect_timeout`].
+    ///
+    /// The curl, ureq and hyper executors honor it. A hyper executor wrapping
+    /// a client built elsewhere (`HyperExecutor::new`) only applies it to
+    /// [`Self::connect_to`] requests. minreq and surf ignore it, see their
+    /// executor docs.
+    pub fn connect_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.result = self.result.map(move |mut pre| {
+            pre.connect_timeout = Some(timeout);
+            pre
         });
         self
     }
 
+    /// Open the connection to the given address or unix socket instead of
+    /// the host in the URI, for example to talk to a local service over a
+    /// unix socket or to a specific server behind a load balancer.
+    ///
+    /// See [`ConnectTo`] for which executors support this.
+    pub fn connect_to(mut self, target: impl Into<ConnectTo>) -> Self {
+        let target = target.into();
+        self.result = self.result.map(move |mut pre| {
+            pre.connect_to = Some(target);
+            pre
+        });
+        self
+    }
+
+    /// Return the body as sent by the server, without decoding it according
+    /// to `Content-Encoding`, for example to save a `.gz` file verbatim.
+    ///
+    /// No `Accept-Encoding` header is added either.
+    /// See [`RequestPre::decompress`].
+    pub fn no_decompress(mut self) -> Self {
+        self.result = self.result.map(|mut pre| {
+            pre.decompress = false;
+            pre
+        });
+        self
+    }
+
+    /// Abort the request when the token is cancelled.
+    ///
+    /// See [`crate::cancel`].
+    pub fn with_cancel(mut self, token: CancelToken) -> Self {
+        self.result = self.result.map(move |mut pre| {
+            pre.cancel = Some(token);
+            pre
+        });
+        self
+    }
+
+    /// The error that will be returned by [`Self::build`] or [`Self::send`],
+    /// for example for an invalid header or URI.
+    ///
+    /// Builder methods don't run after an error, so this is the first one.
+    pub fn error(&self) -> Option<&HttpError> {
+        self.result.as_ref().err()
+    }
+
+    /// Whether building the request failed. See [`Self::error`].
+    pub fn is_err(&self) -> bool {
+        self.result.is_err()
+    }
+
+    /// Finish the request without sending it.
+    ///
+    /// Fails if the client is in strict mode and the request is invalid.
+    /// See [`Client::with_strict_mode`].
     pub fn build(self) -> Result<RequestPre<E::RequestBody>, HttpError> {
-        self.result
+        let mut pre = self.result?;
+        self.client.check_strict(&mut pre.request)?;
+        Ok(pre)
+    }
+
+    /// Build a backend-independent request, for example to compute a
+    /// signature over the canonical request.
+    ///
+    /// Client defaults (like cookies) and middleware are not applied, since
+    /// that happens when the request is sent.
+    ///
+    /// Fails if the executor uses its own body type
+    /// (see [`HttpExecutor::generic_request_body_mut`]).
+    pub fn build_generic(self) -> Result<Request<RequestBody>, HttpError> {
+        let mut request = self.result?.request;
+        let body = self
+            .client
+            .0
+            .exec
+            .generic_request_body_mut(&mut request.body)
+            .map(|body| std::mem::replace(body, RequestBody::Empty))
+            .ok_or_else(|| {
+                HttpError::new(
+                    crate::error::Kind::InvalidRequest,
+                    None,
+                    Some("request body of this executor is not generic".to_string()),
+                )
+            })?;
+        Ok(request.map_body(|_| body))
     }
 
     pub fn send(self) -> <E as HttpExecutor>::Output {
@@ -217,3 +572,65 @@ impl<E: HttpExecutor + Sized> RequestBuilder<E> {
         }
     }
 }
+
+#[cfg(feature = "base64")]
+fn basic_auth_value<U, P>(username: U, password: Option<P>) -> Vec<u8>
+where
+    U: std::fmt::Display,
+    P: std::fmt::Display,
+{
+    use std::io::Write;
+
+    let mut header_value = b"Basic ".to_vec();
+    {
+        let mut encoder = base64::write::EncoderWriter::new(&mut header_value, base64::STANDARD);
+        // The unwraps here are fine because Vec::write* is infallible.
+        write!(encoder, "{}:", username).unwrap();
+        if let Some(password) = password {
+            write!(encoder, "{}", password).unwrap();
+        }
+    }
+    header_value
+}
+
+/// Remove the userinfo (`user:pass@`) from the URI and turn it into a basic
+/// auth header, like curl does.
+///
+/// Credentials set explicitly with an `Authorization` header win over the
+/// URI.
+#[cfg(feature = "base64")]
+pub(crate) fn apply_uri_userinfo(request: &mut Request<impl Sized>) -> Result<(), HttpError> {
+    let Some((userinfo, host)) = request
+        .uri
+        .authority()
+        .and_then(|authority| authority.as_str().rsplit_once('@'))
+        .map(|(userinfo, host)| (userinfo.to_string(), host.to_string()))
+    else {
+        return Ok(());
+    };
+    let decode = |value: &str| {
+        percent_encoding::percent_decode_str(value)
+            .decode_utf8_lossy()
+            .into_owned()
+    };
+    let (username, password) = match userinfo.split_once(':') {
+        Some((username, password)) => (decode(username), Some(decode(password))),
+        None => (decode(&userinfo), None),
+    };
+
+    let mut parts = std::mem::take(&mut request.uri).into_parts();
+    parts.authority = Some(
+        host.parse()
+            .map_err(|err| HttpError::new_invalid_request(err, None))?,
+    );
+    request.uri =
+        Uri::from_parts(parts).map_err(|err| HttpError::new_invalid_request(err, None))?;
+
+    if !request.headers.contains_key(http::header::AUTHORIZATION) {
+        let mut value = HeaderValue::try_from(basic_auth_value(username, password))
+            .map_err(|err| HttpError::new_invalid_request(err, None))?;
+        value.set_sensitive(true);
+        request.headers.insert(http::header::AUTHORIZATION, value);
+    }
+    Ok(())
+}
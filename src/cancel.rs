@@ -0,0 +1,68 @@
+//! Request cancellation.
+//!
+//! A [`CancelToken`] passed to [`crate::RequestBuilder::with_cancel`] aborts
+//! the request when it is triggered, and the response future resolves to an
+//! error for which [`crate::HttpError::is_cancelled`] is true.
+//!
+//! Cancellation covers the request until the response headers are received.
+//! Reading the body is not affected.
+
+use std::{
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+#[derive(Default, Debug)]
+struct State {
+    cancelled: bool,
+    wakers: Vec<Waker>,
+}
+
+/// A cancellation signal that can be shared between tasks.
+///
+/// Clones share the same state.
+#[derive(Clone, Default, Debug)]
+pub struct CancelToken {
+    state: Arc<Mutex<State>>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel all requests that use this token.
+    pub fn cancel(&self) {
+        let wakers = {
+            let mut state = self.state.lock().unwrap();
+            state.cancelled = true;
+            std::mem::take(&mut state.wakers)
+        };
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.lock().unwrap().cancelled
+    }
+
+    /// Check for cancellation, and wake the task when it happens.
+    ///
+    /// For use in executor futures.
+    pub fn poll_cancelled(&self, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.cancelled {
+            return Poll::Ready(());
+        }
+        if !state.wakers.iter().any(|w| w.will_wake(cx.waker())) {
+            state.wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+
+    /// A future that completes when the token is cancelled.
+    pub async fn cancelled(&self) {
+        std::future::poll_fn(|cx| self.poll_cancelled(cx)).await
+    }
+}
@@ -0,0 +1,219 @@
+//! Transparent response decompression.
+//!
+//! Decoding requires the `compression` feature. Without it no encodings are
+//! supported and the helpers return the body unchanged, so executors can
+//! call them unconditionally.
+
+use http::{header, HeaderMap, HeaderValue};
+
+/// The supported encodings, as an `Accept-Encoding` value.
+pub const ACCEPT_ENCODING: &str = "gzip, deflate, br";
+
+/// A supported `Content-Encoding`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    #[cfg(feature = "compression")]
+    Gzip,
+    #[cfg(feature = "compression")]
+    Deflate,
+    #[cfg(feature = "compression")]
+    Brotli,
+}
+
+impl Encoding {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            #[cfg(feature = "compression")]
+            "gzip" | "x-gzip" => Some(Self::Gzip),
+            #[cfg(feature = "compression")]
+            "deflate" => Some(Self::Deflate),
+            #[cfg(feature = "compression")]
+            "br" => Some(Self::Brotli),
+            _ => None,
+        }
+    }
+
+    /// Take the encoding from the response headers.
+    ///
+    /// If the encoding is supported, the `Content-Encoding` and
+    /// `Content-Length` headers are removed, since they don't apply to the
+    /// decoded body.
+    ///
+    /// Returns `None` for identity, unsupported or multiple encodings, in
+    /// which case the headers are left untouched.
+    ///
+    /// `Transfer-Encoding` is never inspected or removed: the transport
+    /// removes the framing like `chunked` before the body is decoded.
+    pub fn take_from_headers(headers: &mut HeaderMap) -> Option<Self> {
+        let mut values = headers.get_all(header::CONTENT_ENCODING).iter();
+        let value = values.next()?;
+        if values.next().is_some() {
+            return None;
+        }
+        let encoding = Self::parse(value.to_str().ok()?)?;
+
+        headers.remove(header::CONTENT_ENCODING);
+        headers.remove(header::CONTENT_LENGTH);
+        Some(encoding)
+    }
+
+    /// Wrap a reader with a decoder for this encoding.
+    #[cfg(feature = "sync")]
+    #[cfg_attr(not(feature = "compression"), allow(unused_variables))]
+    pub fn decode_reader(self, reader: Box<dyn std::io::Read>) -> Box<dyn std::io::Read> {
+        match self {
+            #[cfg(feature = "compression")]
+            Self::Gzip => Box::new(flate2::read::MultiGzDecoder::new(reader)),
+            #[cfg(feature = "compression")]
+            Self::Deflate => Box::new(flate2::read::ZlibDecoder::new(reader)),
+            #[cfg(feature = "compression")]
+            Self::Brotli => Box::new(brotli::Decompressor::new(reader, 8 * 1024)),
+        }
+    }
+
+    /// Wrap a chunk stream with a decoder for this encoding.
+    #[cfg(feature = "async")]
+    #[cfg_attr(not(feature = "compression"), allow(unused_variables))]
+    pub fn decode_stream(self, chunks: crate::DynChunksStream) -> crate::DynChunksStream {
+        #[cfg(feature = "compression")]
+        let reader = {
+            use futures::TryStreamExt;
+            chunks.map_err(std::io::Error::other).into_async_read()
+        };
+
+        match self {
+            #[cfg(feature = "compression")]
+            Self::Gzip => {
+                let mut decoder = async_compression::futures::bufread::GzipDecoder::new(reader);
+                decoder.multiple_members(true);
+                reader_to_chunks(decoder, |decoder| decoder.into_inner())
+            }
+            #[cfg(feature = "compression")]
+            Self::Deflate => reader_to_chunks(
+                async_compression::futures::bufread::ZlibDecoder::new(reader),
+                |decoder| decoder.into_inner(),
+            ),
+            #[cfg(feature = "compression")]
+            Self::Brotli => reader_to_chunks(
+                async_compression::futures::bufread::BrotliDecoder::new(reader),
+                |decoder| decoder.into_inner(),
+            ),
+        }
+    }
+}
+
+/// Add an `Accept-Encoding` header for the supported encodings,
+/// unless one is already set.
+pub(crate) fn add_accept_encoding(headers: &mut HeaderMap) {
+    if cfg!(feature = "compression") {
+        headers
+            .entry(header::ACCEPT_ENCODING)
+            .or_insert(HeaderValue::from_static(ACCEPT_ENCODING));
+    }
+}
+
+/// Compress a request body with gzip.
+///
+/// `Read` bodies are compressed while they are sent.
+#[cfg(feature = "compression")]
+pub(crate) fn gzip_request_body(body: crate::RequestBody) -> crate::RequestBody {
+    use crate::RequestBody;
+    use std::io::Write;
+
+    match body {
+        RequestBody::Empty => RequestBody::Empty,
+        RequestBody::Bytes(bytes) => {
+            let mut encoder = flate2::write::GzEncoder::new(Vec::new(), Default::default());
+            // The unwraps here are fine because Vec::write* is infallible.
+            encoder.write_all(&bytes).unwrap();
+            RequestBody::Bytes(encoder.finish().unwrap())
+        }
+        RequestBody::Read(reader) => RequestBody::Read(Box::new(flate2::read::GzEncoder::new(
+            reader,
+            Default::default(),
+        ))),
+        #[cfg(feature = "async")]
+        RequestBody::Stream(stream) => {
+            use futures::TryStreamExt;
+
+            let reader = stream
+                .into_stream()
+                .map_err(std::io::Error::other)
+                .into_async_read();
+            RequestBody::Stream(crate::BodyStream::new(reader_to_chunks(
+                async_compression::futures::bufread::GzipEncoder::new(reader),
+                |encoder| encoder.into_inner(),
+            )))
+        }
+        #[cfg(not(feature = "async"))]
+        RequestBody::Stream(stream) => match stream.never {},
+    }
+}
+
+/// Decode a response body reader according to the `Content-Encoding` header.
+///
+/// See [`Encoding::take_from_headers`] for how the headers are modified.
+#[cfg(feature = "sync")]
+pub fn decode_reader(
+    headers: &mut HeaderMap,
+    reader: Box<dyn std::io::Read>,
+) -> Box<dyn std::io::Read> {
+    match Encoding::take_from_headers(headers) {
+        Some(encoding) => encoding.decode_reader(reader),
+        None => reader,
+    }
+}
+
+/// Decode a response body stream according to the `Content-Encoding` header.
+///
+/// See [`Encoding::take_from_headers`] for how the headers are modified.
+#[cfg(feature = "async")]
+pub fn decode_stream(
+    headers: &mut HeaderMap,
+    chunks: crate::DynChunksStream,
+) -> crate::DynChunksStream {
+    match Encoding::take_from_headers(headers) {
+        Some(encoding) => encoding.decode_stream(chunks),
+        None => chunks,
+    }
+}
+
+/// Stream the output of a decoder or encoder.
+///
+/// Once it is done, the rest of the input is read from the reader returned
+/// by `into_inner`, so the wrapped stream always reaches its end, for
+/// example to receive trailers.
+#[cfg(all(feature = "async", feature = "compression"))]
+fn reader_to_chunks<D, R>(coder: D, into_inner: fn(D) -> R) -> crate::DynChunksStream
+where
+    D: futures::io::AsyncRead + Unpin + Send + 'static,
+    R: futures::io::AsyncRead + Unpin + Send + 'static,
+{
+    use futures::{AsyncReadExt, StreamExt};
+
+    futures::stream::try_unfold(coder, move |mut coder| async move {
+        let mut buffer = vec![0; 8 * 1024];
+        let count = coder.read(&mut buffer).await.map_err(decode_error)?;
+        if count == 0 {
+            futures::io::copy(into_inner(coder), &mut futures::io::sink())
+                .await
+                .map_err(decode_error)?;
+            return Ok(None);
+        }
+        buffer.truncate(count);
+        Ok(Some((buffer, coder)))
+    })
+    .boxed()
+}
+
+/// Convert a decoder error, preserving errors from the underlying body.
+#[cfg(all(feature = "async", feature = "compression"))]
+fn decode_error(err: std::io::Error) -> crate::HttpError {
+    use crate::HttpError;
+
+    if err.get_ref().is_some_and(|e| e.is::<HttpError>()) {
+        // The type was checked above.
+        return *err.into_inner().unwrap().downcast::<HttpError>().unwrap();
+    }
+    HttpError::new_response_read(Some("could not decode response body".to_string()), err)
+}
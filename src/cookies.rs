@@ -0,0 +1,38 @@
+//! Cookie storage.
+//!
+//! A [`CookieJar`] stores the cookies of responses and provides the cookies
+//! for later requests (see [`crate::Client::new_with_cookie_jar`]). The
+//! default jar is an in-memory [`cookie_store::CookieStore`]. Custom jars
+//! allow sharing sessions between processes, for example in a database, or
+//! evicting cookies in multi-tenant servers.
+
+use std::sync::RwLock;
+
+pub trait CookieJar: Send + Sync + 'static {
+    /// Store the cookies set by a response to `url`.
+    ///
+    /// Malformed `Set-Cookie` headers are skipped before this is called.
+    /// The jar is responsible for attributes like `Domain`, `Path`,
+    /// `Secure` and `Max-Age`.
+    fn store_response_cookies(&self, cookies: Vec<cookie::Cookie<'static>>, url: &url::Url);
+
+    /// The `(name, value)` pairs of the cookies to send with a request to
+    /// `url`.
+    fn get_request_values(&self, url: &url::Url) -> Vec<(String, String)>;
+}
+
+impl CookieJar for RwLock<cookie_store::CookieStore> {
+    fn store_response_cookies(&self, cookies: Vec<cookie::Cookie<'static>>, url: &url::Url) {
+        self.write()
+            .unwrap()
+            .store_response_cookies(cookies.into_iter(), url);
+    }
+
+    fn get_request_values(&self, url: &url::Url) -> Vec<(String, String)> {
+        self.read()
+            .unwrap()
+            .get_request_values(url)
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+}
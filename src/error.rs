@@ -56,8 +56,92 @@ impl HttpError {
         }
     }
 
+    /// The request timed out.
+    pub fn new_timeout(error: impl std::error::Error + Send + Sync + 'static) -> Self {
+        Self {
+            kind: Kind::Timeout,
+            cause: Some(Box::new(error)),
+            message: None,
+        }
+    }
+
+    /// The request was cancelled with a [`crate::cancel::CancelToken`].
+    pub fn new_cancelled() -> Self {
+        Self {
+            kind: Kind::Cancelled,
+            cause: None,
+            message: None,
+        }
+    }
+
+    /// A connection could not be established.
+    pub fn new_connect(error: impl std::error::Error + Send + Sync + 'static) -> Self {
+        Self {
+            kind: Kind::Connect,
+            cause: Some(Box::new(error)),
+            message: None,
+        }
+    }
+
+    /// The request body is larger than the configured limit.
+    ///
+    /// See [`crate::Client::with_max_request_body_size`].
+    pub fn new_request_too_large(limit: u64) -> Self {
+        Self {
+            kind: Kind::RequestTooLarge(limit),
+            cause: None,
+            message: None,
+        }
+    }
+
+    /// The response header section is larger than the backend allows.
+    pub fn new_headers_too_large(error: impl std::error::Error + Send + Sync + 'static) -> Self {
+        Self {
+            kind: Kind::HeadersTooLarge,
+            cause: Some(Box::new(error)),
+            message: None,
+        }
+    }
+
     pub fn is_invalid_request(&self) -> bool {
-        matches!(self.kind, Kind::InvalidRequest) || matches!(self.kind, Kind::InvalidRequest)
+        matches!(self.kind, Kind::InvalidRequest)
+    }
+
+    pub fn is_timeout(&self) -> bool {
+        matches!(self.kind, Kind::Timeout)
+    }
+
+    pub fn is_connect(&self) -> bool {
+        matches!(self.kind, Kind::Connect)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self.kind, Kind::Cancelled)
+    }
+
+    /// The response has a different content type than expected, for
+    /// example an HTML error page instead of JSON.
+    #[cfg(feature = "json")]
+    pub fn is_unexpected_content_type(&self) -> bool {
+        matches!(self.kind, Kind::UnexpectedContentType)
+    }
+
+    pub fn is_request_too_large(&self) -> bool {
+        matches!(self.kind, Kind::RequestTooLarge(_))
+    }
+
+    pub fn is_headers_too_large(&self) -> bool {
+        matches!(self.kind, Kind::HeadersTooLarge)
+    }
+
+    /// Reading the response body failed.
+    pub fn is_body(&self) -> bool {
+        matches!(self.kind, Kind::ResponseRead)
+    }
+
+    /// The error was created by `Response::error_for_status`.
+    pub fn is_status(&self) -> bool {
+        matches!(self.kind, Kind::NonSuccessStatus(_))
     }
 
     pub fn new_custom(message: impl Into<String>) -> Self {
@@ -79,6 +163,32 @@ impl HttpError {
         }
     }
 
+    /// Find an error of type `T` in the chain of causes, for example the
+    /// original `hyper::Error` or `ureq::Error` of the backend.
+    pub fn downcast_ref<T: std::error::Error + 'static>(&self) -> Option<&T> {
+        let mut source = std::error::Error::source(self);
+        while let Some(err) = source {
+            if let Some(err) = err.downcast_ref::<T>() {
+                return Some(err);
+            }
+            source = err.source();
+        }
+        None
+    }
+
+    /// Line and column (both 1-based) where JSON deserialization failed.
+    ///
+    /// The location is also included in the error message.
+    #[cfg(feature = "json")]
+    pub fn json_error_location(&self) -> Option<(usize, usize)> {
+        let err = self.downcast_ref::<serde_json::Error>()?;
+        // serde_json reports line 0 for errors without a location.
+        if err.line() == 0 {
+            return None;
+        }
+        Some((err.line(), err.column()))
+    }
+
     pub fn as_status(&self) -> Option<StatusCode> {
         match self.kind {
             Kind::NonSuccessStatus(s) => Some(s),
@@ -108,18 +218,47 @@ impl std::fmt::Display for HttpError {
                 true
             }
             Kind::NonSuccessStatus(status) => {
-                write!(
-                    f,
-                    "Request failed with status {}({})",
-                    status.as_str(),
-                    status.as_u16()
-                )?;
+                // Non-standard codes (eg 599) have no canonical reason.
+                match status.canonical_reason() {
+                    Some(reason) => write!(
+                        f,
+                        "Request failed with status {} {}",
+                        status.as_u16(),
+                        reason
+                    )?,
+                    None => write!(f, "Request failed with status {}", status.as_u16())?,
+                }
                 true
             }
             Kind::ResponseRead => {
                 write!(f, "could not read response body")?;
                 true
             }
+            Kind::RequestTooLarge(limit) => {
+                write!(f, "request body exceeds the limit of {limit} bytes")?;
+                true
+            }
+            Kind::HeadersTooLarge => {
+                write!(f, "response headers are too large")?;
+                true
+            }
+            #[cfg(feature = "json")]
+            Kind::UnexpectedContentType => {
+                write!(f, "unexpected response content type")?;
+                true
+            }
+            Kind::Timeout => {
+                write!(f, "request timed out")?;
+                true
+            }
+            Kind::Connect => {
+                write!(f, "could not connect")?;
+                true
+            }
+            Kind::Cancelled => {
+                write!(f, "request cancelled")?;
+                true
+            }
             Kind::Http => false,
             Kind::Other => false,
             #[cfg(feature = "json")]
@@ -154,6 +293,26 @@ impl std::fmt::Display for HttpError {
     }
 }
 
+impl From<std::io::Error> for HttpError {
+    fn from(err: std::io::Error) -> Self {
+        Self::new_io(err, None)
+    }
+}
+
+impl From<http::Error> for HttpError {
+    fn from(err: http::Error) -> Self {
+        Self::new_http(err)
+    }
+}
+
+/// Treated as a response deserialization error.
+#[cfg(feature = "json")]
+impl From<serde_json::Error> for HttpError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::new(Kind::InvalidResponseJson, Some(Box::new(err)), None)
+    }
+}
+
 impl std::error::Error for HttpError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         if let Some(e) = self.cause.as_ref() {
@@ -172,7 +331,15 @@ pub(crate) enum Kind {
     #[cfg(feature = "json")]
     InvalidResponseJson,
     NonSuccessStatus(http::StatusCode),
+    /// The request body exceeds the limit, in bytes.
+    RequestTooLarge(u64),
+    HeadersTooLarge,
     ResponseRead,
+    #[cfg(feature = "json")]
+    UnexpectedContentType,
+    Timeout,
+    Connect,
+    Cancelled,
     Http,
     Io,
     Other,
@@ -0,0 +1,228 @@
+//! Skipping interim (`1xx`) responses for sync backends that return them as
+//! the final response, like ureq and minreq for `Expect: 100-continue`.
+
+use std::io::{BufRead, BufReader, Read};
+
+use http::{HeaderMap, HeaderName, HeaderValue, StatusCode, Version};
+
+use crate::{HttpError, ReasonPhrase, Response};
+
+/// Limit for the size of a response head.
+const MAX_HEAD_SIZE: u64 = 64 * 1024;
+
+/// Read the final response that follows an interim response.
+///
+/// `reader` is the rest of the connection after the head of the interim
+/// response, for example the body of a `100 Continue` response as returned
+/// by the backend. Further interim responses are skipped.
+///
+/// The body is delimited by `Content-Length` or the chunked encoding, and
+/// otherwise extends to the end of `reader`. The reason phrase is stored as
+/// a [`ReasonPhrase`] extension.
+pub fn read_final_response(
+    reader: Box<dyn Read>,
+    is_head: bool,
+) -> Result<Response<Box<dyn Read>>, HttpError> {
+    let mut reader = BufReader::new(reader);
+    let (status, version, reason, headers) = loop {
+        let head = read_head(&mut reader)?;
+        // `101 Switching Protocols` ends the response, the other interim
+        // responses are followed by another one.
+        if !head.0.is_informational() || head.0 == StatusCode::SWITCHING_PROTOCOLS {
+            break head;
+        }
+    };
+
+    let chunked = headers
+        .get_all(http::header::TRANSFER_ENCODING)
+        .iter()
+        .next_back()
+        .is_some_and(|value| value.as_bytes().eq_ignore_ascii_case(b"chunked"));
+    let length = headers
+        .get(http::header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok()?.parse::<u64>().ok());
+    let has_body = !is_head
+        && status != StatusCode::NO_CONTENT
+        && status != StatusCode::NOT_MODIFIED
+        && status != StatusCode::SWITCHING_PROTOCOLS;
+    let body: Box<dyn Read> = if !has_body {
+        Box::new(std::io::empty())
+    } else if chunked {
+        Box::new(ChunkedReader {
+            inner: reader,
+            remaining: 0,
+            done: false,
+        })
+    } else if let Some(length) = length {
+        Box::new(LengthReader {
+            inner: reader,
+            remaining: length,
+        })
+    } else {
+        Box::new(reader)
+    };
+
+    let mut extensions = http::Extensions::new();
+    extensions.insert(ReasonPhrase(reason));
+    Ok(Response {
+        uri: None,
+        status,
+        version,
+        headers,
+        extensions,
+        body,
+    })
+}
+
+fn invalid_data(message: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn head_error(err: std::io::Error) -> HttpError {
+    if matches!(
+        err.kind(),
+        std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock
+    ) {
+        return HttpError::new_timeout(err);
+    }
+    HttpError::new_response_read(
+        Some("invalid response after an interim response".to_string()),
+        err,
+    )
+}
+
+/// Read a line without the line break, of at most `limit` bytes in total
+/// with the previous lines.
+fn read_line(reader: &mut impl BufRead, limit: &mut u64) -> std::io::Result<Vec<u8>> {
+    let mut line = Vec::new();
+    reader.take(*limit).read_until(b'\n', &mut line)?;
+    if line.last() != Some(&b'\n') {
+        if line.len() as u64 == *limit {
+            return Err(invalid_data("line too long"));
+        }
+        return Err(unexpected_eof());
+    }
+    *limit -= line.len() as u64;
+    line.pop();
+    if line.last() == Some(&b'\r') {
+        line.pop();
+    }
+    Ok(line)
+}
+
+fn read_head(
+    reader: &mut impl BufRead,
+) -> Result<(StatusCode, Version, String, HeaderMap), HttpError> {
+    let mut limit = MAX_HEAD_SIZE;
+    let line = read_line(reader, &mut limit).map_err(head_error)?;
+    let line = String::from_utf8_lossy(&line);
+    let mut parts = line.splitn(3, ' ');
+    let version = match parts.next() {
+        Some("HTTP/1.0") => Version::HTTP_10,
+        Some("HTTP/1.1") => Version::HTTP_11,
+        _ => return Err(head_error(invalid_data("invalid status line"))),
+    };
+    let status = parts
+        .next()
+        .and_then(|code| StatusCode::from_bytes(code.as_bytes()).ok())
+        .ok_or_else(|| head_error(invalid_data("invalid status code")))?;
+    let reason = parts.next().unwrap_or_default().to_string();
+
+    let mut headers = HeaderMap::new();
+    loop {
+        let line = read_line(reader, &mut limit).map_err(head_error)?;
+        if line.is_empty() {
+            break;
+        }
+        let colon = line
+            .iter()
+            .position(|&b| b == b':')
+            .ok_or_else(|| head_error(invalid_data("invalid header line")))?;
+        let name = HeaderName::from_bytes(&line[..colon])
+            .map_err(|_err| head_error(invalid_data("invalid header name")))?;
+        let value = HeaderValue::from_bytes(line[colon + 1..].trim_ascii())
+            .map_err(|_err| head_error(invalid_data("invalid header value")))?;
+        headers.append(name, value);
+    }
+    Ok((status, version, reason, headers))
+}
+
+fn unexpected_eof() -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::UnexpectedEof,
+        "connection closed before the end of the response",
+    )
+}
+
+/// Body with a `Content-Length`.
+struct LengthReader<R> {
+    inner: R,
+    remaining: u64,
+}
+
+impl<R: Read> Read for LengthReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.remaining == 0 || buf.is_empty() {
+            return Ok(0);
+        }
+        let max = buf
+            .len()
+            .min(usize::try_from(self.remaining).unwrap_or(usize::MAX));
+        let count = self.inner.read(&mut buf[..max])?;
+        if count == 0 {
+            return Err(unexpected_eof());
+        }
+        self.remaining -= count as u64;
+        Ok(count)
+    }
+}
+
+/// Body with the chunked transfer encoding. Trailers are discarded.
+struct ChunkedReader<R> {
+    inner: R,
+    /// Bytes left in the current chunk.
+    remaining: u64,
+    done: bool,
+}
+
+impl<R: BufRead> ChunkedReader<R> {
+    fn read_line(&mut self) -> std::io::Result<Vec<u8>> {
+        let mut limit = MAX_HEAD_SIZE;
+        read_line(&mut self.inner, &mut limit)
+    }
+}
+
+impl<R: BufRead> Read for ChunkedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.done || buf.is_empty() {
+            return Ok(0);
+        }
+        if self.remaining == 0 {
+            let line = self.read_line()?;
+            let size = line.split(|&b| b == b';').next().unwrap_or_default();
+            let size = std::str::from_utf8(size)
+                .ok()
+                .and_then(|size| u64::from_str_radix(size.trim(), 16).ok())
+                .ok_or_else(|| invalid_data("invalid chunk size"))?;
+            if size == 0 {
+                while !self.read_line()?.is_empty() {}
+                self.done = true;
+                return Ok(0);
+            }
+            self.remaining = size;
+        }
+
+        let max = buf
+            .len()
+            .min(usize::try_from(self.remaining).unwrap_or(usize::MAX));
+        let count = self.inner.read(&mut buf[..max])?;
+        if count == 0 {
+            return Err(unexpected_eof());
+        }
+        self.remaining -= count as u64;
+        if self.remaining == 0 && !self.read_line()?.is_empty() {
+            return Err(invalid_data("missing line break after chunk"));
+        }
+        Ok(count)
+    }
+}
@@ -2,16 +2,49 @@ mod builder;
 mod error;
 mod types;
 
+pub mod cancel;
+pub mod compression;
+pub mod metrics;
+pub mod middleware;
+pub mod signing;
+
+#[cfg(feature = "cookies")]
+pub mod cookies;
+
 #[cfg(feature = "test")]
 pub mod test;
 
 #[cfg(feature = "sync")]
 pub mod sync;
 
+#[cfg(feature = "sync")]
+mod interim;
+
 #[cfg(feature = "async")]
 mod async_impl;
 
-use std::sync::Arc;
+#[cfg(feature = "async")]
+pub mod body;
+
+#[cfg(feature = "sse")]
+pub mod sse;
+
+#[cfg(feature = "multipart")]
+pub mod multipart;
+
+#[cfg(any(feature = "sync", feature = "async"))]
+pub mod retry;
+
+#[cfg(feature = "tracing")]
+pub mod trace;
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use self::{
+    metrics::Observer,
+    middleware::{Middleware, MiddlewareRequest},
+    signing::Signer,
+};
 
 pub use http::{
     header::{self, HeaderName, HeaderValue},
@@ -21,13 +54,23 @@ pub use http::{
 pub use self::{
     builder::RequestBuilder,
     error::HttpError,
-    types::{Request, RequestBody, RequestPre, Response},
+    types::{
+        BodyStream, ConnectTo, HeaderOrder, HostPoolStats, IntoUri, PoolConfig, PoolStats,
+        ReasonPhrase, Request, RequestBody, RequestPre, Response,
+    },
 };
 
+#[cfg(feature = "async")]
+pub use self::types::AsyncEmptyResponseBody;
+#[cfg(feature = "sync")]
+pub use self::types::EmptyResponseBody;
+#[cfg(feature = "tls")]
+pub use self::types::{Certificate, Identity, TlsConfig};
+
 #[cfg(feature = "async")]
 pub use self::async_impl::{
-    DynChunksStream, DynClient as AsyncDynClient, DynExecutor as AsyncDynExecutor,
-    DynResponseBody as AsyncDynResponseBody, HttpFuture,
+    DynChunksStream, DynClient as AsyncDynClient, DynExecutor as AsyncDynExecutor, DynReader,
+    DynResponseBody as AsyncDynResponseBody, HttpFuture, RetryExecutor as AsyncRetryExecutor,
 };
 
 pub trait Respond: 'static {
@@ -38,11 +81,89 @@ pub trait Respond: 'static {
     fn into_chunks(self) -> Self::Chunks;
     fn into_chunks_boxed(self: Box<Self>) -> Self::Chunks;
 
+    /// Stream the body as [`bytes::Bytes`] chunks.
+    ///
+    /// A sibling of [`Self::into_chunks_boxed`], so existing implementations
+    /// keep working: the default implementation converts its chunks without
+    /// copying them. Backends that receive the body as `Bytes` can override
+    /// it to avoid the copy into `Vec<u8>` per chunk.
+    /// See `Response::into_bytes_chunks`.
+    #[cfg(feature = "bytes")]
+    fn into_bytes_chunks_boxed(
+        self: Box<Self>,
+    ) -> futures::stream::BoxStream<'static, Result<bytes::Bytes, HttpError>>
+    where
+        Self::Chunks: futures::Stream<Item = Result<Vec<u8>, HttpError>> + Send + 'static,
+    {
+        use futures::{StreamExt, TryStreamExt};
+
+        self.into_chunks_boxed().map_ok(bytes::Bytes::from).boxed()
+    }
+
     fn bytes(self) -> Self::BytesOutput;
     fn bytes_boxed(self: Box<Self>) -> Self::BytesOutput;
 
     fn reader(self) -> Self::Reader;
     fn reader_boxed(self: Box<Self>) -> Self::Reader;
+
+    /// Read the whole body as [`bytes::Bytes`].
+    ///
+    /// The default implementation converts the result of
+    /// [`Self::bytes_boxed`]. Backends that receive the body as `Bytes`
+    /// can override it to avoid copying the body.
+    /// See `Response::bytes_buf_async`.
+    #[cfg(feature = "bytes")]
+    fn bytes_buf_boxed(self: Box<Self>) -> HttpFuture<'static, bytes::Bytes>
+    where
+        Self::BytesOutput: std::future::Future<Output = Result<Vec<u8>, HttpError>> + Send,
+    {
+        let bytes = self.bytes_boxed();
+        Box::pin(async move { Ok(bytes.await?.into()) })
+    }
+
+    /// Read and discard the whole body, returning the number of bytes read.
+    ///
+    /// Reading the body to the end allows backends to reuse the connection.
+    /// The default implementation consumes [`Self::into_chunks_boxed`].
+    /// See `Response::drain_async`.
+    #[cfg(feature = "async")]
+    fn drain_async_boxed(self: Box<Self>) -> HttpFuture<'static, u64>
+    where
+        Self::Chunks: futures::Stream<Item = Result<Vec<u8>, HttpError>> + Send + 'static,
+    {
+        use futures::TryStreamExt;
+
+        let chunks = self.into_chunks_boxed();
+        Box::pin(chunks.try_fold(0, |len, chunk| async move { Ok(len + chunk.len() as u64) }))
+    }
+
+    /// Read and discard the rest of the body, then return the trailers.
+    ///
+    /// Trailers are sent after the body, so they are only available once
+    /// the body was consumed. The default implementation drains the body
+    /// and returns `None`, for backends that don't support trailers.
+    /// See `Response::trailers_async`.
+    #[cfg(feature = "async")]
+    fn trailers_boxed(self: Box<Self>) -> HttpFuture<'static, Option<http::HeaderMap>>
+    where
+        Self::Chunks: futures::Stream<Item = Result<Vec<u8>, HttpError>> + Send + 'static,
+    {
+        let drain = self.drain_async_boxed();
+        Box::pin(async move {
+            drain.await?;
+            Ok(None)
+        })
+    }
+
+    /// Blocking version of [`Self::drain_async_boxed`], for sync backends.
+    /// See `Response::drain_sync`.
+    fn drain_sync_boxed(self: Box<Self>) -> Result<u64, HttpError>
+    where
+        Self::Chunks: Iterator<Item = Result<Vec<u8>, HttpError>>,
+    {
+        self.into_chunks_boxed()
+            .try_fold(0, |len, chunk| Ok(len + chunk?.len() as u64))
+    }
 }
 
 impl<R: Respond + ?Sized> Respond for Box<R> {
@@ -58,6 +179,16 @@ impl<R: Respond + ?Sized> Respond for Box<R> {
         R::into_chunks_boxed(*self)
     }
 
+    #[cfg(feature = "bytes")]
+    fn into_bytes_chunks_boxed(
+        self: Box<Self>,
+    ) -> futures::stream::BoxStream<'static, Result<bytes::Bytes, HttpError>>
+    where
+        Self::Chunks: futures::Stream<Item = Result<Vec<u8>, HttpError>> + Send + 'static,
+    {
+        R::into_bytes_chunks_boxed(*self)
+    }
+
     fn bytes(self) -> Self::BytesOutput {
         self.bytes_boxed()
     }
@@ -73,10 +204,128 @@ impl<R: Respond + ?Sized> Respond for Box<R> {
     fn reader_boxed(self: Box<Self>) -> Self::Reader {
         R::reader_boxed(*self)
     }
+
+    #[cfg(feature = "bytes")]
+    fn bytes_buf_boxed(self: Box<Self>) -> HttpFuture<'static, bytes::Bytes>
+    where
+        Self::BytesOutput: std::future::Future<Output = Result<Vec<u8>, HttpError>> + Send,
+    {
+        R::bytes_buf_boxed(*self)
+    }
+
+    #[cfg(feature = "async")]
+    fn drain_async_boxed(self: Box<Self>) -> HttpFuture<'static, u64>
+    where
+        Self::Chunks: futures::Stream<Item = Result<Vec<u8>, HttpError>> + Send + 'static,
+    {
+        R::drain_async_boxed(*self)
+    }
+
+    #[cfg(feature = "async")]
+    fn trailers_boxed(self: Box<Self>) -> HttpFuture<'static, Option<http::HeaderMap>>
+    where
+        Self::Chunks: futures::Stream<Item = Result<Vec<u8>, HttpError>> + Send + 'static,
+    {
+        R::trailers_boxed(*self)
+    }
+
+    fn drain_sync_boxed(self: Box<Self>) -> Result<u64, HttpError>
+    where
+        Self::Chunks: Iterator<Item = Result<Vec<u8>, HttpError>>,
+    {
+        R::drain_sync_boxed(*self)
+    }
 }
 
+/// A `User-Agent` identifying this crate, for [`Client::with_user_agent`].
+pub const DEFAULT_USER_AGENT: &str = concat!("anyhttp/", env!("CARGO_PKG_VERSION"));
+
+/// Callback that inspects the response head before it is returned.
+///
+/// Executors run it for every response, including error statuses, but not
+/// when the request fails without a response.
 pub type Tapper = Arc<dyn Fn(&mut Response<()>) + Send + Sync>;
 
+/// Add a tap to a request that may already have one. The new tap runs first.
+pub(crate) fn chain_tap<B>(
+    pre: &mut RequestPre<B>,
+    tap: impl Fn(&mut Response<()>) + Send + Sync + 'static,
+) {
+    let inner = pre.tap.take();
+    pre.tap = Some(Arc::new(move |res: &mut Response<()>| {
+        tap(res);
+        if let Some(inner) = &inner {
+            inner(res);
+        }
+    }));
+}
+
+/// Send a single request with an executor, without creating a [`Client`].
+///
+/// For one-shot requests, like in scripts. None of the client features
+/// apply: there is no middleware, cookie jar, default timeout or
+/// `User-Agent`, and no `Accept-Encoding` is added.
+pub fn send_once<E: HttpExecutor>(
+    exec: &E,
+    request: impl Into<Request<E::RequestBody>>,
+) -> E::Output {
+    exec.execute(RequestPre::new(request.into()))
+}
+
+/// Run a tap, turning a panic into an error.
+///
+/// Executors should use this instead of calling the tap directly, so that a
+/// buggy tap fails the request instead of unwinding through the executor.
+pub fn run_tap(tap: &Tapper, res: &mut Response<()>) -> Result<(), HttpError> {
+    catch_panic("tap", || tap(res))
+}
+
+/// Hosts are case-insensitive, so overrides are stored lowercase.
+fn lowercase_hosts(
+    overrides: HashMap<String, std::net::SocketAddr>,
+) -> HashMap<String, std::net::SocketAddr> {
+    overrides
+        .into_iter()
+        .map(|(host, addr)| (host.to_ascii_lowercase(), addr))
+        .collect()
+}
+
+/// Split a `Set-Cookie` value that may contain several folded cookies.
+///
+/// Commas also appear in `Expires` dates, so a comma only starts a new
+/// cookie if it is followed by a `name=value` pair.
+#[cfg(feature = "cookies")]
+fn split_set_cookie(value: &str) -> Vec<&str> {
+    let mut cookies = Vec::new();
+    let mut start = 0;
+    for (index, _) in value.match_indices(',') {
+        let rest = &value[index + 1..];
+        let pair = rest.split([';', ',']).next().unwrap_or_default();
+        let starts_cookie = pair
+            .split_once('=')
+            .is_some_and(|(name, _)| !name.trim().is_empty() && !name.trim().contains(' '));
+        if starts_cookie {
+            cookies.push(value[start..index].trim());
+            start = index + 1;
+        }
+    }
+    cookies.push(value[start..].trim());
+    cookies
+}
+
+fn catch_panic<T>(what: &str, f: impl FnOnce() -> T) -> Result<T, HttpError> {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).map_err(|payload| {
+        let reason = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(|s| s.as_str()));
+        match reason {
+            Some(reason) => HttpError::new_custom(format!("{what} panicked: {reason}")),
+            None => HttpError::new_custom(format!("{what} panicked")),
+        }
+    })
+}
+
 pub trait HttpExecutor {
     type RequestBody;
     type ResponseBody;
@@ -91,6 +340,38 @@ pub trait HttpExecutor {
         let pre2 = pre.map_body(|b| self.request_body_from_generic(b));
         self.execute(pre2)
     }
+
+    /// Access the body as a generic [`RequestBody`], if possible.
+    ///
+    /// Used to expose the body to middleware and to compress it
+    /// (see `RequestBuilder::gzip_body`).
+    /// Executors with a custom body type can return `None`.
+    fn generic_request_body_mut<'a>(
+        &self,
+        body: &'a mut Self::RequestBody,
+    ) -> Option<&'a mut RequestBody> {
+        let _ = body;
+        None
+    }
+
+    /// Connection pool statistics, if the executor tracks them.
+    fn pool_stats(&self) -> Option<PoolStats> {
+        None
+    }
+
+    /// Attach the tracing span of a request to the output of
+    /// [`Self::execute`], which runs inside the span.
+    ///
+    /// Async executors should run their future in the span with
+    /// [`trace::RequestSpan::instrument`], and sync executors should record
+    /// the result with [`trace::RequestSpan::finish`]. By default, the span
+    /// stays open until the executor drops the request, and failures are
+    /// logged without the error.
+    #[cfg(feature = "tracing")]
+    fn instrument_output(&self, output: Self::Output, span: trace::RequestSpan) -> Self::Output {
+        let _ = span;
+        output
+    }
 }
 
 impl<E: HttpExecutor + ?Sized> HttpExecutor for Arc<E> {
@@ -109,18 +390,105 @@ impl<E: HttpExecutor + ?Sized> HttpExecutor for Arc<E> {
     fn execute(&self, pre: RequestPre<Self::RequestBody>) -> Self::Output {
         E::execute(self, pre)
     }
+
+    fn generic_request_body_mut<'a>(
+        &self,
+        body: &'a mut Self::RequestBody,
+    ) -> Option<&'a mut RequestBody> {
+        E::generic_request_body_mut(self, body)
+    }
+
+    fn pool_stats(&self) -> Option<PoolStats> {
+        E::pool_stats(self)
+    }
+
+    #[cfg(feature = "tracing")]
+    fn instrument_output(&self, output: Self::Output, span: trace::RequestSpan) -> Self::Output {
+        E::instrument_output(self, output, span)
+    }
 }
 
+#[derive(Clone)]
 struct ClientInner<E> {
     exec: E,
     #[cfg(feature = "cookies")]
-    cookies: Option<Arc<std::sync::RwLock<cookie_store::CookieStore>>>,
-    #[allow(dead_code)]
+    cookies: Option<Arc<dyn cookies::CookieJar>>,
     tapper: Option<Tapper>,
+    middleware: Vec<Arc<dyn Middleware>>,
+    signer: Option<Arc<dyn Signer>>,
+    observer: Option<Observer>,
+    timeout: Option<Duration>,
+    user_agent: Option<HeaderValue>,
+    max_request_body_size: Option<u64>,
+    host_overrides: HashMap<String, std::net::SocketAddr>,
+    strict_mode: bool,
 }
 
 pub struct Client<E>(Arc<ClientInner<E>>);
 
+/// Builder for a client derived from an existing one.
+///
+/// See [`Client::with_overrides`].
+pub struct ClientBuilder<E> {
+    inner: ClientInner<E>,
+}
+
+impl<E> ClientBuilder<E> {
+    /// Replace the executor, for example with one using different settings.
+    pub fn executor(mut self, exec: E) -> Self {
+        self.inner.exec = exec;
+        self
+    }
+
+    /// Default timeout for requests that don't set their own.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.inner.timeout = Some(timeout);
+        self
+    }
+
+    /// Set or clear the default `User-Agent`.
+    ///
+    /// See [`Client::with_user_agent`].
+    pub fn user_agent(mut self, user_agent: Option<HeaderValue>) -> Self {
+        self.inner.user_agent = user_agent;
+        self
+    }
+
+    /// Set or clear the request body size limit.
+    ///
+    /// See [`Client::with_max_request_body_size`].
+    pub fn max_request_body_size(mut self, limit: Option<u64>) -> Self {
+        self.inner.max_request_body_size = limit;
+        self
+    }
+
+    /// Replace the host overrides.
+    ///
+    /// See [`Client::with_host_overrides`].
+    pub fn host_overrides(mut self, overrides: HashMap<String, std::net::SocketAddr>) -> Self {
+        self.inner.host_overrides = lowercase_hosts(overrides);
+        self
+    }
+
+    /// Enable or disable strict mode.
+    ///
+    /// See [`Client::with_strict_mode`].
+    pub fn strict_mode(mut self, strict: bool) -> Self {
+        self.inner.strict_mode = strict;
+        self
+    }
+
+    /// Add a middleware, after the ones of the original client.
+    pub fn middleware<M: Middleware>(mut self, middleware: M) -> Self {
+        self.inner.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    pub fn build(self) -> Client<E> {
+        Client(Arc::new(self.inner))
+    }
+}
+
 impl<E> Clone for Client<E> {
     fn clone(&self) -> Self {
         Self(self.0.clone())
@@ -137,6 +505,14 @@ where
             #[cfg(feature = "cookies")]
             cookies: None,
             tapper: None,
+            middleware: Vec::new(),
+            signer: None,
+            observer: None,
+            timeout: None,
+            user_agent: None,
+            max_request_body_size: None,
+            host_overrides: HashMap::new(),
+            strict_mode: false,
         }))
     }
 
@@ -157,63 +533,296 @@ where
         self.0.exec.clone()
     }
 
+    /// Add a middleware that runs for every request.
+    ///
+    /// Middleware runs in the order it was added.
+    /// If this client has been cloned, the clones are not affected.
+    pub fn with_middleware<M: Middleware>(mut self, middleware: M) -> Self
+    where
+        E: Clone,
+    {
+        Arc::make_mut(&mut self.0)
+            .middleware
+            .push(Arc::new(middleware));
+        self
+    }
+
+    /// Sign every request with the given signer, replacing any previous one.
+    ///
+    /// The signer runs after middleware. See [`signing`] for the
+    /// requirements on the request body.
+    /// If this client has been cloned, the clones are not affected.
+    pub fn with_signer<S: Signer>(mut self, signer: S) -> Self
+    where
+        E: Clone,
+    {
+        Arc::make_mut(&mut self.0).signer = Some(Arc::new(signer));
+        self
+    }
+
+    /// Report the [`metrics::RequestMetrics`] of every request to the
+    /// observer, replacing any previous one.
+    ///
+    /// If this client has been cloned, the clones are not affected.
+    pub fn with_observer(mut self, observer: Observer) -> Self
+    where
+        E: Clone,
+    {
+        Arc::make_mut(&mut self.0).observer = Some(observer);
+        self
+    }
+
+    /// Send this `User-Agent` with requests that don't set their own,
+    /// replacing any previous one. `None` clears it.
+    ///
+    /// Without a default, each executor sends the user agent of its
+    /// backend, if any. [`DEFAULT_USER_AGENT`] identifies this crate.
+    /// If this client has been cloned, the clones are not affected.
+    pub fn with_user_agent(mut self, user_agent: Option<HeaderValue>) -> Self
+    where
+        E: Clone,
+    {
+        Arc::make_mut(&mut self.0).user_agent = user_agent;
+        self
+    }
+
+    /// Reject requests with a body larger than `limit` bytes, replacing
+    /// any previous limit. `None` removes it.
+    ///
+    /// Requests with a larger `Content-Length`, or a larger
+    /// [`RequestBody::Bytes`] body, fail with an
+    /// [`HttpError::is_request_too_large`] error before they are sent.
+    /// `Read` and `Stream` bodies are checked while they are sent, and the
+    /// error is reported by the executor. The limit applies to the body as
+    /// sent, after middleware and compression.
+    /// If this client has been cloned, the clones are not affected.
+    pub fn with_max_request_body_size(mut self, limit: Option<u64>) -> Self
+    where
+        E: Clone,
+    {
+        Arc::make_mut(&mut self.0).max_request_body_size = limit;
+        self
+    }
+
+    /// Connect to the given address instead of resolving the host, like
+    /// an `/etc/hosts` entry or curl's `--resolve`. Replaces any previous
+    /// overrides.
+    ///
+    /// Hosts are matched case-insensitively, and the port of the address
+    /// is used. The URI and the `Host` header are sent unchanged.
+    /// Requests that set [`RequestBuilder::connect_to`] are not affected.
+    ///
+    /// This uses [`RequestPre::connect_to`], so requests to overridden
+    /// hosts fail with executors that don't support it.
+    /// If this client has been cloned, the clones are not affected.
+    pub fn with_host_overrides(mut self, overrides: HashMap<String, std::net::SocketAddr>) -> Self
+    where
+        E: Clone,
+    {
+        Arc::make_mut(&mut self.0).host_overrides = lowercase_hosts(overrides);
+        self
+    }
+
+    /// Enable or disable strict mode. Disabled by default.
+    ///
+    /// In strict mode, `GET` and `HEAD` requests with a non-empty body
+    /// fail with an [`HttpError::is_invalid_request`] error instead of
+    /// being sent. A body is detected through a non-empty
+    /// [`RequestBody`], a `Read` or `Stream` body, a non-zero
+    /// `Content-Length` or a `Transfer-Encoding` header. Bodies of
+    /// executors with their own body type are only detected through the
+    /// headers.
+    /// If this client has been cloned, the clones are not affected.
+    pub fn with_strict_mode(mut self, strict: bool) -> Self
+    where
+        E: Clone,
+    {
+        Arc::make_mut(&mut self.0).strict_mode = strict;
+        self
+    }
+
+    /// Derive a new client from the configuration of this one.
+    ///
+    /// The new client shares the cookie jar with this client.
+    pub fn with_overrides(&self) -> ClientBuilder<E>
+    where
+        E: Clone,
+    {
+        ClientBuilder {
+            inner: (*self.0).clone(),
+        }
+    }
+
+    /// Connection pool statistics.
+    ///
+    /// This is best-effort: `None` if the executor doesn't track them,
+    /// and the numbers may be approximate.
+    pub fn pool_stats(&self) -> Option<PoolStats> {
+        self.0.exec.pool_stats()
+    }
+
+    /// Create a client that stores cookies in memory, with a
+    /// [`cookie_store::CookieStore`].
     #[cfg(feature = "cookies")]
     pub fn new_with_cookie_jar(exec: E) -> Self {
-        let jar = Arc::new(std::sync::RwLock::new(cookie_store::CookieStore::default()));
+        let jar = std::sync::RwLock::new(cookie_store::CookieStore::default());
+        Self::new_with_custom_cookie_jar(exec, Arc::new(jar))
+    }
 
+    /// Create a client that stores cookies in the given jar.
+    ///
+    /// See [`cookies::CookieJar`].
+    #[cfg(feature = "cookies")]
+    pub fn new_with_custom_cookie_jar(exec: E, jar: Arc<dyn cookies::CookieJar>) -> Self {
         let jar2 = jar.clone();
         let tap: Tapper = Arc::new(move |res: &mut Response<()>| {
-            let mut store = jar.write().unwrap();
-            for header in res.headers.get_all(header::SET_COOKIE) {
-                let opt = std::str::from_utf8(header.as_bytes())
-                    .ok()
-                    .and_then(|v| v.parse::<cookie::Cookie>().ok());
-
-                let url_opt = res
-                    .uri
-                    .as_ref()
-                    .and_then(|u| u.to_string().parse::<url::Url>().ok());
-
-                if let (Some(cookie), Some(url)) = (opt, url_opt) {
-                    store.store_response_cookies(Some(cookie).into_iter(), &url);
-                }
-            }
+            // Cookies are scoped to the URL, so they can't be stored for
+            // responses without an absolute URI.
+            let Some(url) = res
+                .uri
+                .as_ref()
+                .and_then(|u| u.to_string().parse::<url::Url>().ok())
+            else {
+                return;
+            };
+            // Malformed cookies are skipped. Attributes like `Domain`,
+            // `Path`, `Secure` and `Max-Age` are applied by the jar.
+            let cookies = res
+                .headers
+                .get_all(header::SET_COOKIE)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(split_set_cookie)
+                .filter_map(|raw| cookie::Cookie::parse(raw.to_string()).ok())
+                .collect::<Vec<_>>();
+            jar.store_response_cookies(cookies, &url);
         });
         Self(Arc::new(ClientInner {
             exec,
             cookies: Some(jar2),
             tapper: Some(tap),
+            middleware: Vec::new(),
+            signer: None,
+            observer: None,
+            timeout: None,
+            user_agent: None,
+            max_request_body_size: None,
+            host_overrides: HashMap::new(),
+            strict_mode: false,
         }))
     }
 
-    pub fn send(&self, request: Request<E::RequestBody>) -> E::Output {
-        self.send_pre(RequestPre {
-            request,
-            timeout: None,
-            tap: None,
-        })
+    /// Send a request, either a [`Request`] or an [`http::Request`].
+    ///
+    /// The client configuration applies like for requests built with
+    /// [`Self::request`]: middleware, cookies, taps and defaults. With a
+    /// type-erased client (like `sync::DynClient`) this sends an
+    /// `http::Request<RequestBody>` through whatever backend was chosen.
+    pub fn send(&self, request: impl Into<Request<E::RequestBody>>) -> E::Output {
+        self.send_pre(RequestPre::new(request.into()))
     }
 
-    fn map_request(&self, r: Request<E::RequestBody>) -> Request<E::RequestBody> {
-        #[cfg(feature = "cookies")]
-        let mut r = r;
+    /// Reject `GET` and `HEAD` requests with a body if strict mode is on.
+    pub(crate) fn check_strict(
+        &self,
+        request: &mut Request<E::RequestBody>,
+    ) -> Result<(), HttpError> {
+        if !self.0.strict_mode || !(request.method == Method::GET || request.method == Method::HEAD)
+        {
+            return Ok(());
+        }
+
+        let has_body = match self.0.exec.generic_request_body_mut(&mut request.body) {
+            Some(RequestBody::Empty) | None => false,
+            Some(RequestBody::Bytes(bytes)) => !bytes.is_empty(),
+            Some(RequestBody::Read(_)) | Some(RequestBody::Stream(_)) => true,
+        };
+        let content_length = request
+            .headers
+            .get(header::CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u64>().ok());
+        let has_body = has_body
+            || content_length.is_some_and(|len| len > 0)
+            || request.headers.contains_key(header::TRANSFER_ENCODING);
+
+        if has_body {
+            return Err(HttpError::new(
+                error::Kind::InvalidRequest,
+                None,
+                Some(format!("{} requests must not have a body", request.method)),
+            ));
+        }
+        Ok(())
+    }
+
+    fn map_request(&self, pre: &mut RequestPre<E::RequestBody>) -> Result<(), HttpError> {
+        self.check_strict(&mut pre.request)?;
+
+        if pre.timeout.is_none() {
+            pre.timeout = self.0.timeout;
+        }
+
+        if let Some(tapper) = &self.0.tapper {
+            let tapper = tapper.clone();
+            chain_tap(pre, move |res| tapper(res));
+        }
+
+        if pre.connect_to.is_none() && !self.0.host_overrides.is_empty() {
+            if let Some(host) = pre.request.uri.host() {
+                if let Some(addr) = self.0.host_overrides.get(&host.to_ascii_lowercase()) {
+                    pre.connect_to = Some(ConnectTo::Addr(*addr));
+                }
+            }
+        }
+
+        let r = &mut pre.request;
+
+        #[cfg(feature = "base64")]
+        builder::apply_uri_userinfo(r)?;
+
+        if pre.decompress {
+            compression::add_accept_encoding(&mut r.headers);
+        }
+
+        if let Some(user_agent) = &self.0.user_agent {
+            r.headers
+                .entry(header::USER_AGENT)
+                .or_insert_with(|| user_agent.clone());
+        }
+
         #[cfg(feature = "cookies")]
         {
             self.0.cookies.as_ref().and_then(|jar| {
-                if r.headers.contains_key(header::COOKIE) {
-                    return None;
+                // Cookies set on the request win over jar cookies with the
+                // same name.
+                let mut cookies = Vec::new();
+                for value in r.headers.get_all(header::COOKIE) {
+                    cookies.extend(
+                        value
+                            .to_str()
+                            .ok()?
+                            .split(';')
+                            .map(|c| c.trim().to_string())
+                            .filter(|c| !c.is_empty()),
+                    );
                 }
+                let user_names = cookies
+                    .iter()
+                    .map(|c| c.split('=').next().unwrap_or_default().to_string())
+                    .collect::<Vec<_>>();
 
                 let url = r.uri.to_string().parse::<url::Url>().ok()?;
-                let value = jar
-                    .read()
-                    .unwrap()
-                    .get_request_values(&url)
-                    .map(|(name, value)| format!("{name}={value}"))
-                    .collect::<Vec<_>>()
-                    .join("; ")
-                    .parse::<HeaderValue>()
-                    .ok()?;
+                cookies.extend(
+                    jar.get_request_values(&url)
+                        .into_iter()
+                        .filter(|(name, _)| !user_names.iter().any(|n| n == name))
+                        .map(|(name, value)| format!("{name}={value}")),
+                );
+                if cookies.is_empty() {
+                    return None;
+                }
+                let value = cookies.join("; ").parse::<HeaderValue>().ok()?;
 
                 r.headers.insert(header::COOKIE, value);
 
@@ -221,69 +830,130 @@ where
             });
         }
 
-        r
+        if !self.0.middleware.is_empty() {
+            let exec = &self.0.exec;
+            let mut req = MiddlewareRequest::new(r, |body| exec.generic_request_body_mut(body));
+            for middleware in &self.0.middleware {
+                catch_panic("middleware", || middleware.on_request(&mut req))??;
+            }
+        }
+
+        if let Some(signer) = &self.0.signer {
+            signing::sign_request(&self.0.exec, signer.as_ref(), r)?;
+        }
+
+        if pre.max_request_body_size.is_none() {
+            pre.max_request_body_size = self.0.max_request_body_size;
+        }
+        if let Some(limit) = pre.max_request_body_size {
+            let r = &mut pre.request;
+            let content_length = r
+                .headers
+                .get(header::CONTENT_LENGTH)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<u64>().ok());
+            if content_length.is_some_and(|len| len > limit) {
+                return Err(HttpError::new_request_too_large(limit));
+            }
+            if let Some(body) = self.0.exec.generic_request_body_mut(&mut r.body) {
+                let raw = std::mem::replace(body, RequestBody::Empty);
+                *body = raw.limit(limit)?;
+            }
+        }
+
+        let r = &mut pre.request;
+        if let Some(order) = r.extensions.get::<HeaderOrder>() {
+            order.apply(&mut r.headers);
+        }
+
+        Ok(())
     }
 
     pub fn send_pre(&self, mut pre: RequestPre<E::RequestBody>) -> E::Output {
-        pre.request = self.map_request(pre.request);
-        self.0.exec.execute(pre)
+        if let Some(observer) = &self.0.observer {
+            metrics::observe(&mut pre, observer.clone());
+        }
+        match self.map_request(&mut pre) {
+            Ok(()) => {
+                #[cfg(feature = "tracing")]
+                {
+                    let span = trace::request_span(&mut pre);
+                    let output = span.span().in_scope(|| self.0.exec.execute(pre));
+                    self.0.exec.instrument_output(output, span)
+                }
+                #[cfg(not(feature = "tracing"))]
+                self.0.exec.execute(pre)
+            }
+            Err(err) => {
+                #[cfg(feature = "tracing")]
+                trace::invalid_request(&pre, &err);
+                self.0.exec.new_output_error(err)
+            }
+        }
     }
 
     pub fn request<M, U>(&self, method: M, uri: U) -> RequestBuilder<E>
     where
         Method: TryFrom<M>,
         <Method as TryFrom<M>>::Error: Into<http::Error>,
-        Uri: TryFrom<U>,
-        <Uri as TryFrom<U>>::Error: Into<http::Error>,
+        U: IntoUri,
     {
         RequestBuilder::new(self.clone()).method(method).uri(uri)
     }
 
     pub fn get<U>(&self, uri: U) -> RequestBuilder<E>
     where
-        Uri: TryFrom<U>,
-        <Uri as TryFrom<U>>::Error: Into<http::Error>,
+        U: IntoUri,
     {
         self.request(Method::GET, uri)
     }
 
     pub fn head<U>(&self, uri: U) -> RequestBuilder<E>
     where
-        Uri: TryFrom<U>,
-        <Uri as TryFrom<U>>::Error: Into<http::Error>,
+        U: IntoUri,
     {
         self.request(http::Method::HEAD, uri)
     }
 
     pub fn patch<U>(&self, uri: U) -> RequestBuilder<E>
     where
-        Uri: TryFrom<U>,
-        <Uri as TryFrom<U>>::Error: Into<http::Error>,
+        U: IntoUri,
     {
         self.request(http::Method::PATCH, uri)
     }
 
     pub fn post<U>(&self, uri: U) -> RequestBuilder<E>
     where
-        Uri: TryFrom<U>,
-        <Uri as TryFrom<U>>::Error: Into<http::Error>,
+        U: IntoUri,
     {
         self.request(Method::POST, uri)
     }
 
     pub fn put<U>(&self, uri: U) -> RequestBuilder<E>
     where
-        Uri: TryFrom<U>,
-        <Uri as TryFrom<U>>::Error: Into<http::Error>,
+        U: IntoUri,
     {
         self.request(Method::PUT, uri)
     }
 
     pub fn delete<U>(&self, uri: U) -> RequestBuilder<E>
     where
-        Uri: TryFrom<U>,
-        <Uri as TryFrom<U>>::Error: Into<http::Error>,
+        U: IntoUri,
     {
         self.request(Method::DELETE, uri)
     }
+
+    pub fn options<U>(&self, uri: U) -> RequestBuilder<E>
+    where
+        U: IntoUri,
+    {
+        self.request(Method::OPTIONS, uri)
+    }
+
+    pub fn trace<U>(&self, uri: U) -> RequestBuilder<E>
+    where
+        U: IntoUri,
+    {
+        self.request(Method::TRACE, uri)
+    }
 }
@@ -0,0 +1,96 @@
+//! Per-request metrics.
+//!
+//! An [`Observer`] set with [`crate::Client::with_observer`] is called once
+//! for every request, with its [`RequestMetrics`].
+//!
+//! The duration covers the time until the response headers were received,
+//! since reading the body is up to the caller. For async executors this
+//! includes the whole response future, not just the dispatch.
+//!
+//! The duration is also available from the response itself, with
+//! [`crate::Response::elapsed`]. None of the executors expose finer timings
+//! like DNS resolution or connecting, so only the total is recorded.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use http::{Method, StatusCode, Uri};
+
+use crate::RequestPre;
+
+#[derive(Clone, Debug)]
+pub struct RequestMetrics {
+    pub method: Method,
+    /// The request URI without userinfo, so credentials are never reported.
+    pub uri: Uri,
+    /// `None` if the request failed or was dropped before a response was
+    /// received.
+    pub status: Option<StatusCode>,
+    pub duration: Duration,
+}
+
+pub type Observer = Arc<dyn Fn(&RequestMetrics) + Send + Sync>;
+
+/// Response extension with the time until the response headers were
+/// received. See [`crate::Response::elapsed`].
+#[derive(Clone, Copy, Debug)]
+pub struct Elapsed(pub Duration);
+
+/// Calls the observer when the request is dropped by the executor.
+struct ObserverGuard {
+    observer: Observer,
+    method: Method,
+    uri: Uri,
+    start: Instant,
+    response: Mutex<Option<(StatusCode, Duration)>>,
+}
+
+impl Drop for ObserverGuard {
+    fn drop(&mut self) {
+        let response = *self.response.lock().unwrap();
+        let metrics = RequestMetrics {
+            method: self.method.clone(),
+            uri: self.uri.clone(),
+            status: response.map(|(status, _)| status),
+            duration: response
+                .map(|(_, duration)| duration)
+                .unwrap_or_else(|| self.start.elapsed()),
+        };
+        // Panicking in drop could abort, so a failing observer is ignored.
+        let _ = crate::catch_panic("observer", || (self.observer)(&metrics));
+    }
+}
+
+/// Chain a tap onto the request that reports its metrics to the observer.
+pub(crate) fn observe<B>(pre: &mut RequestPre<B>, observer: Observer) {
+    let guard = ObserverGuard {
+        observer,
+        method: pre.request.method.clone(),
+        uri: without_userinfo(&pre.request.uri),
+        start: Instant::now(),
+        response: Mutex::new(None),
+    };
+    crate::chain_tap(pre, move |res| {
+        let elapsed = guard.start.elapsed();
+        *guard.response.lock().unwrap() = Some((res.status, elapsed));
+        res.extensions.insert(Elapsed(elapsed));
+    });
+}
+
+fn without_userinfo(uri: &Uri) -> Uri {
+    let Some((_, host)) = uri
+        .authority()
+        .and_then(|authority| authority.as_str().rsplit_once('@'))
+    else {
+        return uri.clone();
+    };
+    let mut parts = uri.clone().into_parts();
+    parts.authority = host.parse().ok();
+    if parts.authority.is_none() {
+        // Such requests fail anyway, report only the path.
+        parts.scheme = None;
+    }
+    Uri::from_parts(parts).unwrap_or_default()
+}
@@ -0,0 +1,69 @@
+//! Client middleware.
+//!
+//! Middleware runs for every request sent through a [`crate::Client`], after
+//! the client has applied its own defaults (like cookies), and can inspect or
+//! modify the request before it is handed to the executor.
+
+use http::{HeaderMap, Method, Uri};
+
+use crate::{Extensions, HttpError, RequestBody};
+
+/// A backend-agnostic view of an outgoing request.
+pub struct MiddlewareRequest<'a> {
+    pub method: &'a mut Method,
+    pub uri: &'a mut Uri,
+    pub headers: &'a mut HeaderMap,
+    pub extensions: &'a mut Extensions,
+    body: Option<&'a RequestBody>,
+}
+
+impl<'a> MiddlewareRequest<'a> {
+    pub(crate) fn new<B>(
+        request: &'a mut crate::Request<B>,
+        body: impl FnOnce(&'a mut B) -> Option<&'a mut RequestBody>,
+    ) -> Self {
+        Self {
+            method: &mut request.method,
+            uri: &mut request.uri,
+            headers: &mut request.headers,
+            extensions: &mut request.extensions,
+            body: body(&mut request.body).map(|b| &*b),
+        }
+    }
+
+    /// The request body.
+    ///
+    /// `None` if the executor uses its own body type, which can't be
+    /// inspected. See [`crate::HttpExecutor::generic_request_body_mut`].
+    pub fn body(&self) -> Option<&RequestBody> {
+        self.body
+    }
+
+    /// The request body bytes, for example for computing a signature.
+    ///
+    /// `None` if the body is not available (see [`Self::body`]) or is
+    /// a streaming body.
+    pub fn body_bytes(&self) -> Option<&[u8]> {
+        match self.body? {
+            RequestBody::Empty => Some(&[]),
+            RequestBody::Bytes(bytes) => Some(bytes),
+            RequestBody::Read(_) | RequestBody::Stream(_) => None,
+        }
+    }
+}
+
+pub trait Middleware: Send + Sync + 'static {
+    /// Called before the request is sent.
+    ///
+    /// Returning an error aborts the request.
+    fn on_request(&self, request: &mut MiddlewareRequest<'_>) -> Result<(), HttpError>;
+}
+
+impl<F> Middleware for F
+where
+    F: Fn(&mut MiddlewareRequest<'_>) -> Result<(), HttpError> + Send + Sync + 'static,
+{
+    fn on_request(&self, request: &mut MiddlewareRequest<'_>) -> Result<(), HttpError> {
+        self(request)
+    }
+}
@@ -0,0 +1,214 @@
+//! Parsing of `multipart/*` response bodies, like `multipart/mixed` or
+//! `multipart/byteranges`.
+
+use futures::{Stream, StreamExt};
+use http::{header::HeaderName, HeaderMap, HeaderValue};
+
+use crate::{error::Kind, HttpError, Respond, Response};
+
+/// A single part of a multipart body.
+#[derive(Clone, Debug, Default)]
+pub struct Part {
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Waiting for a boundary delimiter line.
+    Delimiter,
+    Headers,
+    Body,
+    /// The closing delimiter was seen.
+    Done,
+}
+
+/// Incremental parser for the multipart format of RFC 2046.
+struct PartParser {
+    /// The boundary, prefixed with `\r\n--`.
+    delimiter: Vec<u8>,
+    buffer: Vec<u8>,
+    /// How much of the buffer was already searched for the delimiter.
+    searched: usize,
+    state: State,
+    headers: HeaderMap,
+}
+
+impl PartParser {
+    fn new(boundary: &str) -> Self {
+        Self {
+            delimiter: format!("\r\n--{boundary}").into_bytes(),
+            buffer: Vec::new(),
+            searched: 0,
+            state: State::Delimiter,
+            headers: HeaderMap::new(),
+        }
+    }
+
+    /// Parse the next complete part from the buffered data.
+    ///
+    /// Returns `None` if more data is needed or the body is complete.
+    fn next_part(&mut self) -> Result<Option<Part>, HttpError> {
+        loop {
+            match self.state {
+                State::Delimiter => {
+                    // The first delimiter may be at the very start of the
+                    // body, without a preceding line break.
+                    let dash_boundary = &self.delimiter[2..];
+                    let start = if self.buffer.starts_with(dash_boundary) {
+                        0
+                    } else {
+                        match find(&self.buffer, &self.delimiter) {
+                            Some(pos) => pos + 2,
+                            None => return Ok(None),
+                        }
+                    };
+                    let rest = &self.buffer[start + dash_boundary.len()..];
+                    if rest.len() < 2 {
+                        return Ok(None);
+                    }
+                    if rest.starts_with(b"--") {
+                        self.state = State::Done;
+                        self.buffer = Vec::new();
+                        return Ok(None);
+                    }
+                    // The delimiter line may end with transport padding.
+                    let Some(end) = find(rest, b"\r\n") else {
+                        return Ok(None);
+                    };
+                    let consumed = start + dash_boundary.len() + end + 2;
+                    self.buffer.drain(..consumed);
+                    self.state = State::Headers;
+                }
+                State::Headers => {
+                    let end = if self.buffer.starts_with(b"\r\n") {
+                        0
+                    } else {
+                        match find(&self.buffer, b"\r\n\r\n") {
+                            Some(pos) => pos + 2,
+                            None => return Ok(None),
+                        }
+                    };
+                    self.headers = parse_headers(&self.buffer[..end])?;
+                    self.buffer.drain(..end + 2);
+                    self.state = State::Body;
+                }
+                State::Body => {
+                    // The delimiter may start in the already searched data,
+                    // if it was split across chunks.
+                    let from = self
+                        .searched
+                        .saturating_sub(self.delimiter.len().saturating_sub(1));
+                    let Some(pos) = find(&self.buffer[from..], &self.delimiter) else {
+                        self.searched = self.buffer.len();
+                        return Ok(None);
+                    };
+                    let pos = from + pos;
+                    let body = self.buffer.drain(..pos).collect();
+                    // Keep the delimiter without the line break, which
+                    // belongs to it.
+                    self.buffer.drain(..2);
+                    self.searched = 0;
+                    self.state = State::Delimiter;
+                    return Ok(Some(Part {
+                        headers: std::mem::take(&mut self.headers),
+                        body,
+                    }));
+                }
+                State::Done => return Ok(None),
+            }
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+fn invalid_multipart(message: &str) -> HttpError {
+    HttpError::new(Kind::ResponseRead, None, Some(message.to_string()))
+}
+
+fn parse_headers(raw: &[u8]) -> Result<HeaderMap, HttpError> {
+    let mut headers = HeaderMap::new();
+    for line in raw.split(|b| *b == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.is_empty() {
+            continue;
+        }
+        let pos = line
+            .iter()
+            .position(|b| *b == b':')
+            .ok_or_else(|| invalid_multipart("invalid multipart part header"))?;
+        let name = HeaderName::from_bytes(line[..pos].trim_ascii())
+            .map_err(|_| invalid_multipart("invalid multipart part header name"))?;
+        let value = HeaderValue::from_bytes(line[pos + 1..].trim_ascii())
+            .map_err(|_| invalid_multipart("invalid multipart part header value"))?;
+        headers.append(name, value);
+    }
+    Ok(headers)
+}
+
+/// Take the boundary from a `multipart/*` content type.
+fn boundary(headers: &HeaderMap) -> Result<String, HttpError> {
+    let content_type = headers
+        .get(http::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    let mut params = content_type.split(';');
+    let essence = params.next().unwrap_or_default().trim();
+    if !essence.to_ascii_lowercase().starts_with("multipart/") {
+        return Err(invalid_multipart("response is not a multipart body"));
+    }
+    params
+        .filter_map(|param| param.split_once('='))
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case("boundary"))
+        .map(|(_, value)| value.trim().trim_matches('"').to_string())
+        .filter(|boundary| !boundary.is_empty() && boundary.len() <= 70)
+        .ok_or_else(|| invalid_multipart("multipart content type without a valid boundary"))
+}
+
+impl<B> Response<B>
+where
+    B: Respond,
+    <B as Respond>::BytesOutput:
+        std::future::Future<Output = Result<Vec<u8>, HttpError>> + Send + 'static,
+    <B as Respond>::Chunks: Stream<Item = Result<Vec<u8>, HttpError>> + Send + 'static,
+{
+    /// Parse a `multipart/*` body into its parts.
+    ///
+    /// The boundary is taken from the `Content-Type` header. The preamble
+    /// and epilogue are skipped.
+    ///
+    /// Parts are yielded as soon as they are complete, but the body of each
+    /// part is buffered in memory.
+    pub fn multipart_async(self) -> impl Stream<Item = Result<Part, HttpError>> + Send + 'static {
+        let parser = match boundary(&self.headers) {
+            Ok(boundary) => PartParser::new(&boundary),
+            Err(err) => return futures::stream::once(async move { Err(err) }).left_stream(),
+        };
+        let chunks = Box::pin(self.body.into_chunks());
+
+        futures::stream::try_unfold((chunks, parser), |(mut chunks, mut parser)| async move {
+            loop {
+                if let Some(part) = parser.next_part()? {
+                    return Ok(Some((part, (chunks, parser))));
+                }
+                if parser.state == State::Done {
+                    return Ok(None);
+                }
+                match chunks.next().await {
+                    Some(chunk) => parser.buffer.extend(chunk?),
+                    None => {
+                        return Err(invalid_multipart(
+                            "multipart body ended before the closing boundary",
+                        ))
+                    }
+                }
+            }
+        })
+        .right_stream()
+    }
+}
@@ -0,0 +1,231 @@
+//! Automatic retries.
+//!
+//! Retries are implemented by executor wrappers:
+//! `sync::RetryExecutor` and `AsyncRetryExecutor`.
+//! Only requests with a replayable body (not [`RequestBody::Read`] or
+//! [`RequestBody::Stream`]) are retried.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use http::{HeaderMap, Method, StatusCode, Uri, Version};
+
+use crate::{
+    cancel::CancelToken, ConnectTo, HttpError, Request, RequestBody, RequestPre, Response, Tapper,
+};
+
+/// Decides which failed requests are retried.
+///
+/// Transport errors and `502`, `503` and `504` responses are retried.
+///
+/// By default only idempotent requests (see [`is_idempotent`]) are retried,
+/// since a failed request may still have been processed by the server.
+/// Use [`RetryPolicy::retry_non_idempotent`] to retry all methods.
+///
+/// Clones share the same retry budget.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    budget: Option<Arc<RetryBudget>>,
+    retry_on_reset: bool,
+    retry_non_idempotent: bool,
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            budget: None,
+            retry_on_reset: false,
+            retry_non_idempotent: false,
+        }
+    }
+
+    /// Limit the total number of retries with a token bucket.
+    ///
+    /// Every request adds `ratio` tokens to the bucket and every retry
+    /// takes one. When the bucket is empty, no retries are made even if
+    /// `max_retries` allows them, which prevents retry storms when a
+    /// backend is overloaded.
+    ///
+    /// A ratio of `0.1` allows one retry per ten requests in the long run.
+    /// The bucket starts with [`RetryPolicy::BUDGET_RESERVE`] tokens, which
+    /// is also its capacity.
+    pub fn with_budget(mut self, ratio: f32) -> Self {
+        self.budget = Some(Arc::new(RetryBudget {
+            ratio: ratio.max(0.0),
+            tokens: Mutex::new(Self::BUDGET_RESERVE),
+        }));
+        self
+    }
+
+    /// Also retry when the connection fails after the response headers were
+    /// received, while reading the first chunk of the body.
+    ///
+    /// To make this possible, the first chunk is read before the response
+    /// is returned. Once the caller has received any body data, failures
+    /// are not retried.
+    ///
+    /// Like all retries, this only applies to idempotent requests unless
+    /// [`Self::retry_non_idempotent`] is enabled.
+    pub fn retry_on_reset(mut self, enabled: bool) -> Self {
+        self.retry_on_reset = enabled;
+        self
+    }
+
+    /// Also retry requests with non-idempotent methods, like `POST` and
+    /// `PATCH`.
+    ///
+    /// Only enable this if the server deduplicates requests, for example
+    /// with an idempotency key, or if duplicate requests are harmless.
+    pub fn retry_non_idempotent(mut self, enabled: bool) -> Self {
+        self.retry_non_idempotent = enabled;
+        self
+    }
+
+    /// Initial size and capacity of the retry budget.
+    pub const BUDGET_RESERVE: f32 = 10.0;
+
+    /// Check if the first body chunk should be read before returning the
+    /// response. See [`Self::retry_on_reset`].
+    pub(crate) fn peek_body(&self, method: &Method) -> bool {
+        self.retry_on_reset && self.max_retries > 0 && self.allows_method(method)
+    }
+
+    fn allows_method(&self, method: &Method) -> bool {
+        self.retry_non_idempotent || is_idempotent(method)
+    }
+
+    pub(crate) fn on_request(&self) {
+        if let Some(budget) = &self.budget {
+            budget.deposit();
+        }
+    }
+
+    /// Check if another attempt should be made, and take a token from
+    /// the budget if so.
+    pub(crate) fn should_retry<B>(
+        &self,
+        retries: u32,
+        method: &Method,
+        result: &Result<Response<B>, HttpError>,
+    ) -> bool {
+        if retries >= self.max_retries || !self.allows_method(method) {
+            return false;
+        }
+        let retryable = match result {
+            Ok(res) => matches!(
+                res.status,
+                StatusCode::BAD_GATEWAY
+                    | StatusCode::SERVICE_UNAVAILABLE
+                    | StatusCode::GATEWAY_TIMEOUT
+            ),
+            Err(err) => !err.is_invalid_request() && !err.is_cancelled(),
+        };
+        retryable && self.budget.as_ref().is_none_or(|b| b.withdraw())
+    }
+}
+
+/// Check if a request method is idempotent, so that retrying it can't cause
+/// duplicate side effects.
+pub fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS | Method::TRACE
+    )
+}
+
+#[derive(Debug)]
+struct RetryBudget {
+    ratio: f32,
+    tokens: Mutex<f32>,
+}
+
+impl RetryBudget {
+    fn deposit(&self) {
+        let mut tokens = self.tokens.lock().unwrap();
+        *tokens = (*tokens + self.ratio).min(RetryPolicy::BUDGET_RESERVE);
+    }
+
+    fn withdraw(&self) -> bool {
+        let mut tokens = self.tokens.lock().unwrap();
+        if *tokens >= 1.0 {
+            *tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// A copy of a request that can be sent again.
+///
+/// Request extensions can't be cloned, so they are not included.
+pub(crate) struct ReplayableRequest {
+    method: Method,
+    uri: Uri,
+    version: Version,
+    headers: HeaderMap,
+    body: Option<Vec<u8>>,
+    timeout: Option<Duration>,
+    deadline: Option<Instant>,
+    connect_timeout: Option<Duration>,
+    connect_to: Option<ConnectTo>,
+    cancel: Option<CancelToken>,
+    tap: Option<Tapper>,
+    decompress: bool,
+}
+
+impl ReplayableRequest {
+    /// Returns `None` for streaming bodies.
+    pub(crate) fn new(pre: &RequestPre<RequestBody>) -> Option<Self> {
+        let r = &pre.request;
+        let body = match &r.body {
+            RequestBody::Empty => None,
+            RequestBody::Bytes(bytes) => Some(bytes.clone()),
+            RequestBody::Read(_) | RequestBody::Stream(_) => return None,
+        };
+        Some(Self {
+            method: r.method.clone(),
+            uri: r.uri.clone(),
+            version: r.version,
+            headers: r.headers.clone(),
+            body,
+            timeout: pre.timeout,
+            deadline: pre.deadline,
+            connect_timeout: pre.connect_timeout,
+            connect_to: pre.connect_to.clone(),
+            cancel: pre.cancel.clone(),
+            tap: pre.tap.clone(),
+            decompress: pre.decompress,
+        })
+    }
+
+    pub(crate) fn method(&self) -> &Method {
+        &self.method
+    }
+
+    pub(crate) fn to_pre(&self) -> RequestPre<RequestBody> {
+        let body = match &self.body {
+            Some(bytes) => RequestBody::Bytes(bytes.clone()),
+            None => RequestBody::Empty,
+        };
+        let mut request = Request::new(body);
+        request.method = self.method.clone();
+        request.uri = self.uri.clone();
+        request.version = self.version;
+        request.headers = self.headers.clone();
+
+        let mut pre = RequestPre::new(request);
+        pre.timeout = self.timeout;
+        pre.deadline = self.deadline;
+        pre.connect_timeout = self.connect_timeout;
+        pre.connect_to = self.connect_to.clone();
+        pre.cancel = self.cancel.clone();
+        pre.tap = self.tap.clone();
+        pre.decompress = self.decompress;
+        pre
+    }
+}
@@ -0,0 +1,119 @@
+//! Request signing.
+//!
+//! A [`Signer`] runs for every request sent through a [`crate::Client`]
+//! (see [`crate::Client::with_signer`]), after middleware, so that it sees
+//! the final request.
+//!
+//! Signatures usually cover the body, so signing requires a buffered body:
+//! requests with a [`RequestBody::Read`] or [`RequestBody::Stream`] body, or
+//! with an executor that uses its own body type, fail.
+
+use crate::{HttpError, HttpExecutor, Request, RequestBody};
+
+pub trait Signer: Send + Sync + 'static {
+    /// Sign the request, usually by adding headers.
+    ///
+    /// `body` is the complete request body.
+    fn sign(&self, request: &mut Request<()>, body: &[u8]) -> Result<(), HttpError>;
+}
+
+impl<F> Signer for F
+where
+    F: Fn(&mut Request<()>, &[u8]) -> Result<(), HttpError> + Send + Sync + 'static,
+{
+    fn sign(&self, request: &mut Request<()>, body: &[u8]) -> Result<(), HttpError> {
+        self(request, body)
+    }
+}
+
+/// Run a signer on a request.
+pub(crate) fn sign_request<E: HttpExecutor>(
+    exec: &E,
+    signer: &dyn Signer,
+    request: &mut Request<E::RequestBody>,
+) -> Result<(), HttpError> {
+    let body: &[u8] = match exec.generic_request_body_mut(&mut request.body) {
+        Some(RequestBody::Empty) => &[],
+        Some(RequestBody::Bytes(bytes)) => bytes,
+        Some(RequestBody::Read(_) | RequestBody::Stream(_)) | None => {
+            return Err(HttpError::new(
+                crate::error::Kind::InvalidRequest,
+                None,
+                Some("signing requires a buffered request body".to_string()),
+            ))
+        }
+    };
+
+    let mut parts = Request {
+        method: std::mem::take(&mut request.method),
+        uri: std::mem::take(&mut request.uri),
+        version: request.version,
+        headers: std::mem::take(&mut request.headers),
+        extensions: std::mem::take(&mut request.extensions),
+        body: (),
+    };
+    let result = crate::catch_panic("signer", || signer.sign(&mut parts, body));
+
+    request.method = parts.method;
+    request.uri = parts.uri;
+    request.version = parts.version;
+    request.headers = parts.headers;
+    request.extensions = parts.extensions;
+    result?
+}
+
+/// Signs requests with HMAC-SHA256.
+///
+/// The signature covers the method, the path and query and the body:
+///
+/// ```text
+/// {METHOD}\n{path?query}\n{body}
+/// ```
+///
+/// and is sent base64-encoded in the `x-signature` header (see
+/// [`HmacSigner::header`]).
+#[cfg(feature = "hmac")]
+pub struct HmacSigner {
+    key: ring::hmac::Key,
+    header: http::HeaderName,
+}
+
+#[cfg(feature = "hmac")]
+impl HmacSigner {
+    pub fn new(key: &[u8]) -> Self {
+        Self {
+            key: ring::hmac::Key::new(ring::hmac::HMAC_SHA256, key),
+            header: http::HeaderName::from_static("x-signature"),
+        }
+    }
+
+    /// Set the header that holds the signature.
+    pub fn header(mut self, header: http::HeaderName) -> Self {
+        self.header = header;
+        self
+    }
+}
+
+#[cfg(feature = "hmac")]
+impl Signer for HmacSigner {
+    fn sign(&self, request: &mut Request<()>, body: &[u8]) -> Result<(), HttpError> {
+        let path = request
+            .uri
+            .path_and_query()
+            .map(|p| p.as_str())
+            .unwrap_or("/");
+
+        let mut ctx = ring::hmac::Context::with_key(&self.key);
+        ctx.update(request.method.as_str().as_bytes());
+        ctx.update(b"\n");
+        ctx.update(path.as_bytes());
+        ctx.update(b"\n");
+        ctx.update(body);
+        let signature = base64::encode(ctx.sign().as_ref());
+
+        // Base64 is always a valid header value.
+        let value = http::HeaderValue::from_str(&signature).unwrap();
+        request.headers.insert(self.header.clone(), value);
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+//! Server-Sent Events (`text/event-stream`) support.
+
+use futures::{Stream, TryStreamExt};
+
+use crate::{HttpError, Respond, Response};
+
+/// A single server-sent event.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Event {
+    /// The last event id seen in the stream.
+    pub id: Option<String>,
+    /// The event type.
+    /// `None` means the default `message` type.
+    pub event: Option<String>,
+    /// The event data.
+    /// Multiple `data:` lines are joined with `\n`.
+    pub data: String,
+    /// Reconnection time in milliseconds.
+    pub retry: Option<u64>,
+}
+
+/// Line based parser for the `text/event-stream` format.
+#[derive(Default)]
+struct EventParser {
+    last_id: Option<String>,
+    event: Option<String>,
+    data: String,
+    retry: Option<u64>,
+    has_data: bool,
+}
+
+impl EventParser {
+    /// Feed a single line (without line terminator).
+    ///
+    /// Returns an event when the line completes one.
+    fn push_line(&mut self, line: &str) -> Option<Event> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        if line.starts_with(':') {
+            // Comment.
+            return None;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+
+        match field {
+            "data" => {
+                self.data.push_str(value);
+                self.data.push('\n');
+                self.has_data = true;
+            }
+            "event" => {
+                self.event = Some(value.to_string());
+            }
+            "id" if !value.contains('\0') => {
+                self.last_id = Some(value.to_string());
+            }
+            "retry" => {
+                if let Ok(retry) = value.parse() {
+                    self.retry = Some(retry);
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<Event> {
+        let event = self.event.take();
+        let retry = self.retry.take();
+        let mut data = std::mem::take(&mut self.data);
+
+        if !std::mem::take(&mut self.has_data) {
+            return None;
+        }
+        if data.ends_with('\n') {
+            data.pop();
+        }
+
+        Some(Event {
+            id: self.last_id.clone(),
+            event,
+            data,
+            retry,
+        })
+    }
+}
+
+impl<B> Response<B>
+where
+    B: Respond,
+    <B as Respond>::BytesOutput:
+        std::future::Future<Output = Result<Vec<u8>, HttpError>> + Send + 'static,
+    <B as Respond>::Chunks: Stream<Item = Result<Vec<u8>, HttpError>> + Send + 'static,
+{
+    /// Parse the body as a `text/event-stream`.
+    ///
+    /// An incomplete event at the end of the stream is discarded.
+    pub fn events_async(self) -> impl Stream<Item = Result<Event, HttpError>> + Send + 'static {
+        let mut parser = EventParser::default();
+        let mut first = true;
+
+        self.lines_async().try_filter_map(move |line| {
+            let line = if std::mem::take(&mut first) {
+                line.strip_prefix('\u{feff}').unwrap_or(&line).to_string()
+            } else {
+                line
+            };
+            futures::future::ready(Ok(parser.push_line(&line)))
+        })
+    }
+}
@@ -1,11 +1,26 @@
 use std::sync::Arc;
 
+use http::StatusCode;
+
+pub use crate::interim::read_final_response;
+
 use crate::{
-    error::{self, HttpError},
+    error::HttpError,
+    retry::{ReplayableRequest, RetryPolicy},
     types::Response,
-    HttpExecutor, RequestBody, RequestPre, Respond,
+    HttpExecutor, IntoUri, Method, PoolStats, RequestBody, RequestPre, Respond,
 };
 
+/// Executors that apply the request timeout to the body report its expiry
+/// as a `TimedOut` error of the reader.
+fn map_read_error(err: std::io::Error) -> HttpError {
+    if err.kind() == std::io::ErrorKind::TimedOut {
+        HttpError::new_timeout(err)
+    } else {
+        HttpError::new_response_read(None, err)
+    }
+}
+
 pub enum GenericResponseBody {
     Read(Box<dyn std::io::Read>),
 }
@@ -15,10 +30,28 @@ impl Respond for GenericResponseBody {
     type BytesOutput = Result<Vec<u8>, HttpError>;
     type Reader = Box<dyn std::io::Read>;
 
+    /// Yields the data of each read of up to 8 KiB, ending after the first
+    /// error.
     fn into_chunks(self) -> Self::Chunks {
-        // FIXME: proper implemenetation
-        let bytes = self.bytes();
-        Box::new(Some(bytes).into_iter())
+        let mut reader = Some(self.reader());
+        Box::new(std::iter::from_fn(move || loop {
+            let mut buffer = vec![0; 8 * 1024];
+            match reader.as_mut()?.read(&mut buffer) {
+                Ok(0) => {
+                    reader = None;
+                    return None;
+                }
+                Ok(count) => {
+                    buffer.truncate(count);
+                    return Some(Ok(buffer));
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(err) => {
+                    reader = None;
+                    return Some(Err(map_read_error(err)));
+                }
+            }
+        }))
     }
 
     fn into_chunks_boxed(self: Box<Self>) -> Self::Chunks {
@@ -30,9 +63,7 @@ impl Respond for GenericResponseBody {
             GenericResponseBody::Read(mut r) => {
                 let mut buf = Vec::new();
 
-                r.read_to_end(&mut buf).map_err(|err| {
-                    HttpError::new_io(err, Some("could not read response body".to_string()))
-                })?;
+                r.read_to_end(&mut buf).map_err(map_read_error)?;
 
                 Ok(buf)
             }
@@ -52,6 +83,10 @@ impl Respond for GenericResponseBody {
     fn reader_boxed(self: Box<Self>) -> Self::Reader {
         (*self).reader()
     }
+
+    fn drain_sync_boxed(self: Box<Self>) -> Result<u64, HttpError> {
+        std::io::copy(&mut self.reader(), &mut std::io::sink()).map_err(map_read_error)
+    }
 }
 
 struct DynRespondWrapper<R>(R);
@@ -92,6 +127,10 @@ where
     fn reader_boxed(self: Box<Self>) -> Self::Reader {
         (*self).reader()
     }
+
+    fn drain_sync_boxed(self: Box<Self>) -> Result<u64, HttpError> {
+        Box::new(self.0).drain_sync_boxed()
+    }
 }
 
 struct DynWrapper<E>(E);
@@ -129,6 +168,27 @@ where
     fn execute_generic(&self, pre: RequestPre<RequestBody>) -> Self::Output {
         self.execute(pre)
     }
+
+    fn generic_request_body_mut<'a>(
+        &self,
+        body: &'a mut RequestBody,
+    ) -> Option<&'a mut RequestBody> {
+        Some(body)
+    }
+
+    fn pool_stats(&self) -> Option<PoolStats> {
+        self.0.pool_stats()
+    }
+
+    #[cfg(feature = "tracing")]
+    fn instrument_output(
+        &self,
+        output: Self::Output,
+        span: crate::trace::RequestSpan,
+    ) -> Self::Output {
+        span.finish(&output);
+        output
+    }
 }
 
 pub type DynExecutor = Arc<
@@ -141,6 +201,134 @@ pub type DynExecutor = Arc<
 
 pub type DynClient = super::Client<DynExecutor>;
 
+/// Erase the type of a sync executor, for example to pick the backend at
+/// startup.
+///
+/// A [`DynClient`] can be created from the result with `From`.
+pub fn into_dyn_executor<E>(exec: E) -> DynExecutor
+where
+    E: HttpExecutor + 'static,
+    E::ResponseBody: Respond<BytesOutput = Result<Vec<u8>, HttpError>>,
+    E::Output: Into<Result<Response<E::ResponseBody>, HttpError>>,
+    <E::ResponseBody as Respond>::Chunks: Iterator<Item = Result<Vec<u8>, HttpError>> + 'static,
+    <E::ResponseBody as Respond>::Reader: std::io::Read + 'static,
+{
+    Arc::new(DynWrapper(exec))
+}
+
+impl From<DynExecutor> for DynClient {
+    fn from(exec: DynExecutor) -> Self {
+        Self::new(exec)
+    }
+}
+
+/// Executor wrapper that retries failed requests.
+///
+/// See [`crate::retry`].
+pub struct RetryExecutor<E> {
+    exec: E,
+    policy: RetryPolicy,
+}
+
+impl<E> RetryExecutor<E> {
+    pub fn new(exec: E, policy: RetryPolicy) -> Self {
+        Self { exec, policy }
+    }
+}
+
+impl<E> RetryExecutor<E>
+where
+    E: HttpExecutor,
+    E::Output: Into<Result<Response<E::ResponseBody>, HttpError>>,
+    E::ResponseBody: Respond,
+    <E::ResponseBody as Respond>::Reader: std::io::Read + 'static,
+{
+    fn attempt(
+        &self,
+        pre: RequestPre<RequestBody>,
+        peek: bool,
+    ) -> Result<Response<GenericResponseBody>, HttpError> {
+        use std::io::Read;
+
+        let (res, body) = self.exec.execute_generic(pre).into()?.take_body();
+        let mut reader = body.reader();
+
+        let reader: Box<dyn std::io::Read> = if peek {
+            let mut first = vec![0; 8 * 1024];
+            let count = loop {
+                match reader.read(&mut first) {
+                    Ok(count) => break count,
+                    Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+                    Err(err) => return Err(map_read_error(err)),
+                }
+            };
+            first.truncate(count);
+            Box::new(std::io::Cursor::new(first).chain(reader))
+        } else {
+            Box::new(reader)
+        };
+
+        Ok(res.map_body(|_| GenericResponseBody::Read(reader)))
+    }
+}
+
+impl<E> HttpExecutor for RetryExecutor<E>
+where
+    E: HttpExecutor,
+    E::Output: Into<Result<Response<E::ResponseBody>, HttpError>>,
+    E::ResponseBody: Respond,
+    <E::ResponseBody as Respond>::Reader: std::io::Read + 'static,
+{
+    type RequestBody = RequestBody;
+    type ResponseBody = GenericResponseBody;
+    type Output = Result<Response<GenericResponseBody>, HttpError>;
+
+    fn request_body_from_generic(&self, body: RequestBody) -> Self::RequestBody {
+        body
+    }
+
+    fn new_output_error(&self, error: HttpError) -> Self::Output {
+        Err(error)
+    }
+
+    fn execute(&self, pre: RequestPre<Self::RequestBody>) -> Self::Output {
+        self.policy.on_request();
+        let replay = ReplayableRequest::new(&pre);
+        let peek = replay.is_some() && self.policy.peek_body(&pre.request.method);
+        let mut result = self.attempt(pre, peek);
+
+        if let Some(replay) = replay {
+            let mut retries = 0;
+            while self.policy.should_retry(retries, replay.method(), &result) {
+                retries += 1;
+                result = self.attempt(replay.to_pre(), peek);
+            }
+        }
+        result
+    }
+
+    fn generic_request_body_mut<'a>(
+        &self,
+        body: &'a mut RequestBody,
+    ) -> Option<&'a mut RequestBody> {
+        Some(body)
+    }
+
+    fn pool_stats(&self) -> Option<PoolStats> {
+        self.exec.pool_stats()
+    }
+
+    #[cfg(feature = "tracing")]
+    fn instrument_output(
+        &self,
+        output: Self::Output,
+        span: crate::trace::RequestSpan,
+    ) -> Self::Output {
+        span.finish(&output);
+        output
+    }
+}
+
 impl<E> super::Client<E>
 where
     E: HttpExecutor + 'static,
@@ -150,8 +338,32 @@ where
     <E::ResponseBody as Respond>::Reader: std::io::Read + 'static,
 {
     pub fn new_dyn_sync(exec: E) -> super::Client<DynExecutor> {
-        let dyn_exec: DynExecutor = Arc::new(DynWrapper(exec));
-        super::Client::new(dyn_exec)
+        super::Client::new(into_dyn_executor(exec))
+    }
+}
+
+impl<E> super::Client<E>
+where
+    E: HttpExecutor,
+    E::Output: Into<Result<Response<E::ResponseBody>, HttpError>>,
+    E::ResponseBody: Respond,
+    <E::ResponseBody as Respond>::Reader: std::io::Read,
+{
+    /// Send a request and only return the response status.
+    ///
+    /// The body is drained to allow connection re-use.
+    /// Useful for health checks.
+    pub fn status_sync<M, U>(&self, method: M, uri: U) -> Result<StatusCode, HttpError>
+    where
+        Method: TryFrom<M>,
+        <Method as TryFrom<M>>::Error: Into<http::Error>,
+        U: IntoUri,
+    {
+        let (res, body) = self.request(method, uri).send().into()?.take_body();
+
+        std::io::copy(&mut body.reader(), &mut std::io::sink()).map_err(map_read_error)?;
+
+        Ok(res.status)
     }
 }
 
@@ -163,11 +375,153 @@ where
         self.body.bytes()
     }
 
+    /// Iterate over the body chunks, like `res.body.into_chunks()`.
+    pub fn chunks_sync(self) -> impl Iterator<Item = Result<Vec<u8>, HttpError>>
+    where
+        B::Chunks: Iterator<Item = Result<Vec<u8>, HttpError>>,
+    {
+        self.body.into_chunks()
+    }
+
+    /// Read and discard the whole body, returning the number of bytes read.
+    ///
+    /// For callers that only need the status or headers: reading the body
+    /// to the end allows backends to reuse the connection.
+    pub fn drain_sync(self) -> Result<u64, HttpError>
+    where
+        B::Chunks: Iterator<Item = Result<Vec<u8>, HttpError>>,
+    {
+        Box::new(self.body).drain_sync_boxed()
+    }
+
+    /// Read the whole body into a new response, so the status, headers and
+    /// extensions stay accessible after the body was read, and the body can
+    /// be inspected multiple times.
+    pub fn buffered_sync(self) -> Result<Response<Vec<u8>>, HttpError> {
+        let (res, body) = self.take_body();
+        let bytes = body.bytes()?;
+        Ok(res.map_body(|_| bytes))
+    }
+
+    /// Read the body and decode it according to the `Content-Encoding`
+    /// header, for responses that were not decompressed by the executor.
+    ///
+    /// Bodies with an unsupported encoding are returned as-is. Only the
+    /// encodings enabled by the `compression` feature are decoded.
+    pub fn bytes_decoded_sync(self) -> Result<Vec<u8>, HttpError> {
+        use std::io::Read;
+
+        let (mut res, body) = self.take_body();
+        let bytes = body.bytes()?;
+        let Some(encoding) = crate::compression::Encoding::take_from_headers(&mut res.headers)
+        else {
+            return Ok(bytes);
+        };
+        let mut decoded = Vec::new();
+        encoding
+            .decode_reader(Box::new(std::io::Cursor::new(bytes)))
+            .read_to_end(&mut decoded)
+            .map_err(|err| {
+                HttpError::new_response_read(
+                    Some("could not decode response body".to_string()),
+                    err,
+                )
+            })?;
+        Ok(decoded)
+    }
+
+    /// Like [`Response::error_for_status`], but on failure reads up to
+    /// `limit` bytes of the body and includes them in the error message.
+    ///
+    /// The body is only consumed if the status is not a success.
+    pub fn error_for_status_with_body_sync(self, limit: usize) -> Result<Self, HttpError>
+    where
+        B::Reader: std::io::Read,
+    {
+        use std::io::Read;
+
+        if self.status.is_success() {
+            return Ok(self);
+        }
+        let (res, body) = self.take_body();
+        let mut snippet = Vec::new();
+        // The status error is more useful than a read error, so the body
+        // is best-effort.
+        let _ = body.reader().take(limit as u64).read_to_end(&mut snippet);
+        Err(res.status_error_with_body(&snippet))
+    }
+
+    /// Read the body through a buffered reader, for example to parse
+    /// framing with [`std::io::BufRead::read_line`].
+    pub fn into_buf_read(self) -> std::io::BufReader<B::Reader>
+    where
+        B::Reader: std::io::Read,
+    {
+        std::io::BufReader::new(self.body.reader())
+    }
+
+    /// Iterate over the body as text lines.
+    ///
+    /// Lines are split on `\n`, with a trailing `\r` removed.
+    /// Trailing data without a final newline is returned as the last line.
+    pub fn lines_sync(self) -> impl Iterator<Item = Result<String, HttpError>>
+    where
+        B::Reader: std::io::Read,
+    {
+        use std::io::BufRead;
+
+        std::io::BufReader::new(self.body.reader())
+            .lines()
+            .map(|res| {
+                res.map_err(|err| {
+                    HttpError::new_response_read(Some("could not read line".to_string()), err)
+                })
+            })
+    }
+
     #[cfg(feature = "json")]
     pub fn json_sync<T: serde::de::DeserializeOwned>(self) -> Result<T, HttpError> {
         let bytes = self.bytes_sync()?;
-        serde_json::from_slice(&bytes).map_err(|err| {
-            HttpError::new(error::Kind::InvalidResponseJson, Some(Box::new(err)), None)
-        })
+        serde_json::from_slice(&bytes).map_err(|err| crate::types::json_body_error(err, &bytes))
+    }
+
+    /// Like [`Self::json_sync`], but first check that the `Content-Type`
+    /// is a JSON media type.
+    ///
+    /// Fails with an [`HttpError::is_unexpected_content_type`] error
+    /// otherwise, for example for an HTML error page, instead of a
+    /// confusing parse error. The body is not read in that case.
+    #[cfg(feature = "json")]
+    pub fn json_if_json_sync<T: serde::de::DeserializeOwned>(self) -> Result<T, HttpError> {
+        self.expect_json_content_type()?;
+        self.json_sync()
+    }
+
+    /// Read the body and pass it to `f`, for deserializing into types that
+    /// borrow from the body, like `&str` fields, without copying.
+    ///
+    /// Errors returned by `f` are reported like invalid JSON in
+    /// [`Self::json_sync`].
+    #[cfg(feature = "json")]
+    pub fn with_json_bytes_sync<T, F>(self, f: F) -> Result<T, HttpError>
+    where
+        F: FnOnce(&[u8]) -> Result<T, serde_json::Error>,
+    {
+        let bytes = self.bytes_sync()?;
+        f(&bytes).map_err(|err| crate::types::json_body_error(err, &bytes))
+    }
+
+    /// Deserialize a JSON body that starts with a fixed prefix.
+    ///
+    /// Some APIs prefix JSON with `)]}',\n` to prevent JSON hijacking.
+    /// Returns an error if the prefix is missing.
+    #[cfg(feature = "json")]
+    pub fn json_with_prefix_strip_sync<T: serde::de::DeserializeOwned>(
+        self,
+        prefix: &str,
+    ) -> Result<T, HttpError> {
+        let bytes = self.bytes_sync()?;
+        let json = crate::types::strip_json_prefix(&bytes, prefix)?;
+        serde_json::from_slice(json).map_err(|err| crate::types::json_body_error(err, json))
     }
 }
@@ -2,29 +2,488 @@ use std::{future::Future, sync::Arc};
 
 use futures::{Stream, StreamExt};
 
-use crate::{HttpError, HttpExecutor, Respond, Response};
+use http::StatusCode;
 
-const TEST_URL: &'static str = "127.0.0.1:44444";
+use crate::{HeaderName, HeaderValue, HttpError, HttpExecutor, Method, Respond, Response};
 
-fn start_test_server() -> Arc<tiny_http::Server> {
-    let server = Arc::new(tiny_http::Server::http(TEST_URL).unwrap());
+/// Address of the test server.
+///
+/// The server also acts as a plain http proxy, see
+/// [`test_async_proxy_executor`].
+pub const TEST_URL: &str = "127.0.0.1:44444";
 
-    {
+struct TestServer {
+    server: Arc<tiny_http::Server>,
+    thread: std::thread::JoinHandle<()>,
+}
+
+impl TestServer {
+    /// Stop the server.
+    ///
+    /// tiny_http closes the listener in the background, so the port may
+    /// still be in use for a moment.
+    fn stop(self) {
+        self.server.unblock();
+        self.thread.join().unwrap();
+    }
+}
+
+fn start_test_server() -> TestServer {
+    // The previous server may not have released the port yet.
+    let mut attempts = 0;
+    let server = loop {
+        match tiny_http::Server::http(TEST_URL) {
+            Ok(server) => break Arc::new(server),
+            Err(_) if attempts < 50 => {
+                attempts += 1;
+                std::thread::sleep(std::time::Duration::from_millis(20));
+            }
+            Err(err) => panic!("could not start test server: {err}"),
+        }
+    };
+
+    let thread = {
         let server = server.clone();
         std::thread::spawn(move || {
-            for request in server.incoming_requests() {
-                let out = serde_json::to_vec(&serde_json::json!({
-                    "url": request.url(),
-                }))
+            for mut request in server.incoming_requests() {
+                let res = handle_test_request(&mut request);
+                // Fails if the client aborts the response.
+                let _ = request.respond(res);
+            }
+        })
+    };
+    TestServer { server, thread }
+}
+
+/// Address of a raw server that closes the first connection right after the
+/// response headers, and answers `ok` on the second one.
+const RESET_URL: &str = "127.0.0.1:44445";
+
+fn start_reset_server() -> std::thread::JoinHandle<()> {
+    use std::io::{BufRead, Write};
+
+    let listener = std::net::TcpListener::bind(RESET_URL).unwrap();
+    std::thread::spawn(move || {
+        let responses: [&[u8]; 2] = [
+            b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\n",
+            b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+        ];
+        for response in responses {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = std::io::BufReader::new(stream);
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            reader.get_mut().write_all(response).unwrap();
+        }
+    })
+}
+
+/// Address of a raw server that answers `Expect: 100-continue` with an
+/// interim response as specified. tiny_http sends a `Content-Length` with
+/// it, which clients without interim response support can't skip.
+const CONTINUE_URL: &str = "127.0.0.1:44446";
+
+/// Serve one request on [`CONTINUE_URL`], answering with the received body
+/// and `Expect` header as JSON, in a chunked body.
+fn start_continue_server() -> std::thread::JoinHandle<()> {
+    use std::io::{BufRead, Read, Write};
+
+    let listener = std::net::TcpListener::bind(CONTINUE_URL).unwrap();
+    std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = std::io::BufReader::new(stream);
+        let mut expect = None;
+        let mut length = 0;
+        let mut line = String::new();
+        while reader.read_line(&mut line).unwrap() > 2 {
+            let (name, value) = line.split_once(':').unwrap_or_default();
+            let value = value.trim().to_string();
+            match name.to_ascii_lowercase().as_str() {
+                "expect" => expect = Some(value),
+                "content-length" => length = value.parse().unwrap(),
+                _ => {}
+            }
+            line.clear();
+        }
+        if expect.is_some() {
+            reader
+                .get_mut()
+                .write_all(b"HTTP/1.1 100 Continue\r\n\r\n")
+                .unwrap();
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+
+        let json = serde_json::json!({
+            "body": String::from_utf8(body).unwrap(),
+            "expect": expect,
+        })
+        .to_string();
+        let (first, second) = json.split_at(json.len() / 2);
+        let response = format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\n\
+             transfer-encoding: chunked\r\nconnection: close\r\n\r\n\
+             {:x}\r\n{first}\r\n{:x}\r\n{second}\r\n0\r\n\r\n",
+            first.len(),
+            second.len(),
+        );
+        reader.get_mut().write_all(response.as_bytes()).unwrap();
+    })
+}
+
+/// Repeated to build the bodies of `/chunked` and `/gzip-chunked`.
+const CHUNKED_BODY: &str = "chunked body\n";
+
+/// Reader that sleeps before its first read.
+struct DelayedReader<R> {
+    delay: Option<std::time::Duration>,
+    inner: R,
+}
+
+impl<R: std::io::Read> std::io::Read for DelayedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if let Some(delay) = self.delay.take() {
+            std::thread::sleep(delay);
+        }
+        self.inner.read(buf)
+    }
+}
+
+fn handle_test_request(request: &mut tiny_http::Request) -> tiny_http::ResponseBox {
+    if let Some(code) = request.url().strip_prefix("/status/") {
+        let code = code.parse::<u16>().unwrap_or(500);
+        return tiny_http::Response::empty(code).boxed();
+    }
+    if request.url().starts_with("/signature") {
+        let signature = request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("x-signature"))
+            .map(|h| h.value.to_string())
+            .unwrap_or_default();
+        return tiny_http::Response::from_string(signature).boxed();
+    }
+    if request.url() == "/invalid" {
+        return tiny_http::Response::from_string("invalid field x")
+            .with_status_code(400)
+            .boxed();
+    }
+    if request.url().starts_with("http://") {
+        // Absolute-form request, sent to a proxy.
+        let auth = request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("proxy-authorization"))
+            .map(|h| h.value.to_string());
+        let out = serde_json::to_vec(&serde_json::json!({
+            "url": request.url(),
+            "proxy_authorization": auth,
+        }))
+        .unwrap();
+        return tiny_http::Response::from_data(out).boxed();
+    }
+    if request.url() == "/events" {
+        let body = "\u{feff}: comment\nretry: 1000\ndata: first\n\n\
+                    id: 2\nevent: update\ndata: line1\ndata:line2\n\n\
+                    data: incomplete";
+        return tiny_http::Response::from_string(body).boxed();
+    }
+    if request.url() == "/headers" {
+        let names = request
+            .headers()
+            .iter()
+            .map(|h| h.field.as_str().as_str().to_lowercase())
+            .collect::<Vec<_>>();
+        let out = serde_json::to_vec(&names).unwrap();
+        return tiny_http::Response::from_data(out).boxed();
+    }
+    if request.url() == "/authorization" {
+        let header = |name: &'static str| {
+            request
+                .headers()
+                .iter()
+                .find(|h| h.field.equiv(name))
+                .map(|h| h.value.to_string())
+        };
+        let out = serde_json::to_vec(&serde_json::json!({
+            "authorization": header("authorization"),
+            "host": header("host"),
+        }))
+        .unwrap();
+        return tiny_http::Response::from_data(out).boxed();
+    }
+    if request.url() == "/user-agent" {
+        let user_agent = request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("user-agent"))
+            .map(|h| h.value.to_string())
+            .unwrap_or_default();
+        return tiny_http::Response::from_string(user_agent).boxed();
+    }
+    if request.url() == "/multipart" {
+        let body = "preamble\r\n\
+                    --sep\r\n\
+                    Content-Range: bytes 0-2/10\r\n\
+                    \r\n\
+                    abc\r\n\
+                    --sep \r\n\
+                    \r\n\
+                    line\r\n--other\r\n\
+                    --sep--\r\n\
+                    epilogue";
+        let content_type =
+            tiny_http::Header::from_bytes("content-type", "multipart/mixed; boundary=\"sep\"")
                 .unwrap();
+        return tiny_http::Response::from_string(body)
+            .with_header(content_type)
+            .boxed();
+    }
+    if request.url() == "/method" {
+        let content_length = request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("content-length"))
+            .map(|h| h.value.to_string());
+        let out = serde_json::to_vec(&serde_json::json!({
+            "method": request.method().as_str(),
+            "content_length": content_length,
+        }))
+        .unwrap();
+        return tiny_http::Response::from_data(out).boxed();
+    }
+    if request.url() == "/created" {
+        let location = tiny_http::Header::from_bytes("location", "/items/1").unwrap();
+        let content_type =
+            tiny_http::Header::from_bytes("content-type", "text/plain; charset=utf-8").unwrap();
+        let vary = |value: &str| tiny_http::Header::from_bytes("vary", value).unwrap();
+        return tiny_http::Response::from_string("created")
+            .with_status_code(201)
+            .with_header(location)
+            .with_header(content_type)
+            .with_header(vary("accept"))
+            .with_header(vary("accept-encoding"))
+            .boxed();
+    }
+    if request.url() == "/host" {
+        let host = request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("host"))
+            .map(|h| h.value.to_string())
+            .unwrap_or_default();
+        return tiny_http::Response::from_string(host).boxed();
+    }
+    if request.url() == "/login" {
+        let cookie = tiny_http::Header::from_bytes("set-cookie", "session=abc").unwrap();
+        return tiny_http::Response::from_string("unauthorized")
+            .with_status_code(401)
+            .with_header(cookie)
+            .boxed();
+    }
+    if request.url() == "/set-cookies" {
+        let cookies = [
+            // Folded, with a comma in the date.
+            "a=1; Path=/, old=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT",
+            "other=1; Domain=example.com",
+            "=broken",
+            "temp=1; Max-Age=3600",
+            "gone=1; Max-Age=0",
+            "sub=1; Path=/sub",
+        ];
+        let mut res = tiny_http::Response::from_string("ok");
+        for cookie in cookies {
+            res = res.with_header(tiny_http::Header::from_bytes("set-cookie", cookie).unwrap());
+        }
+        return res.boxed();
+    }
+    if request.url() == "/cookies" {
+        let cookies = request
+            .headers()
+            .iter()
+            .filter(|h| h.field.equiv("cookie"))
+            .map(|h| h.value.to_string())
+            .collect::<Vec<_>>();
+        let out = serde_json::to_vec(&cookies).unwrap();
+        return tiny_http::Response::from_data(out).boxed();
+    }
+    #[cfg(feature = "compression")]
+    if ["/gzip", "/deflate", "/br"].contains(&request.url()) {
+        use std::io::Write;
 
-                let res = tiny_http::Response::from_data(out).with_status_code(200);
+        let accept = request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("accept-encoding"))
+            .map(|h| h.value.to_string());
+        let out = serde_json::to_vec(&serde_json::json!({
+            "url": request.url(),
+            "accept_encoding": accept,
+        }))
+        .unwrap();
 
-                request.respond(res).unwrap();
+        let encoding = &request.url()[1..];
+        let data = match encoding {
+            "gzip" => {
+                let mut enc = flate2::write::GzEncoder::new(Vec::new(), Default::default());
+                enc.write_all(&out).unwrap();
+                enc.finish().unwrap()
             }
-        });
+            "deflate" => {
+                let mut enc = flate2::write::ZlibEncoder::new(Vec::new(), Default::default());
+                enc.write_all(&out).unwrap();
+                enc.finish().unwrap()
+            }
+            _ => {
+                let mut enc = brotli::CompressorWriter::new(Vec::new(), 4096, 5, 22);
+                enc.write_all(&out).unwrap();
+                enc.into_inner()
+            }
+        };
+        let header = tiny_http::Header::from_bytes("content-encoding", encoding).unwrap();
+        return tiny_http::Response::from_data(data)
+            .with_header(header)
+            .boxed();
+    }
+    // Without a length, the body is sent with `Transfer-Encoding: chunked`.
+    if request.url() == "/chunked" {
+        let data = CHUNKED_BODY.repeat(1000).into_bytes();
+        return tiny_http::Response::new(
+            200.into(),
+            Vec::new(),
+            std::io::Cursor::new(data),
+            None,
+            None,
+        )
+        .boxed();
+    }
+    #[cfg(feature = "compression")]
+    if request.url() == "/gzip-chunked" {
+        use std::io::Write;
+
+        let mut enc = flate2::write::GzEncoder::new(Vec::new(), Default::default());
+        enc.write_all(CHUNKED_BODY.repeat(1000).as_bytes()).unwrap();
+        let header = tiny_http::Header::from_bytes("content-encoding", "gzip").unwrap();
+        return tiny_http::Response::new(
+            200.into(),
+            vec![header],
+            std::io::Cursor::new(enc.finish().unwrap()),
+            None,
+            None,
+        )
+        .boxed();
+    }
+    #[cfg(feature = "compression")]
+    if request.url() == "/gunzip" {
+        use std::io::Read;
+
+        let encoding = request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("content-encoding"))
+            .map(|h| h.value.to_string());
+        let mut body = String::new();
+        flate2::read::GzDecoder::new(request.as_reader())
+            .read_to_string(&mut body)
+            .unwrap();
+        let out = serde_json::to_vec(&serde_json::json!({
+            "content_encoding": encoding,
+            "body": body,
+        }))
+        .unwrap();
+        return tiny_http::Response::from_data(out).boxed();
+    }
+    if request.url() == "/form" {
+        let content_type = request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("content-type"))
+            .map(|h| h.value.to_string());
+        let mut body = String::new();
+        std::io::Read::read_to_string(request.as_reader(), &mut body).unwrap();
+        let out = serde_json::to_vec(&serde_json::json!({
+            "content_type": content_type,
+            "body": body,
+        }))
+        .unwrap();
+        return tiny_http::Response::from_data(out).boxed();
+    }
+    if request.url() == "/prefixed-json" {
+        return tiny_http::Response::from_string(")]}',\n{\"a\":1}").boxed();
+    }
+    if request.url() == "/slow" {
+        std::thread::sleep(std::time::Duration::from_millis(300));
+    }
+    // The head and the start of the body are sent right away, since they
+    // don't fit into the buffer of the server, and the rest after 300ms.
+    if request.url() == "/slow-body" {
+        use std::io::Read;
+
+        let start = std::io::Cursor::new(vec![b'a'; 64 * 1024]);
+        let rest = DelayedReader {
+            delay: Some(std::time::Duration::from_millis(300)),
+            inner: std::io::Cursor::new(b"done".to_vec()),
+        };
+        return tiny_http::Response::new(
+            200.into(),
+            Vec::new(),
+            start.chain(rest),
+            Some(64 * 1024 + 4),
+            None,
+        )
+        .boxed();
+    }
+    if request.url() == "/large" {
+        return tiny_http::Response::from_data(vec![b'a'; 4 * 1024 * 1024]).boxed();
+    }
+    if request.url() == "/json-array" {
+        let items = (0..2000)
+            .map(|i| format!("{{\"i\": {i}}}"))
+            .collect::<Vec<_>>();
+        let body = format!("[{}]\n", items.join(",\n "));
+        return tiny_http::Response::from_string(body).boxed();
     }
-    server
+    if request.url() == "/problem-json" {
+        let content_type =
+            tiny_http::Header::from_bytes("content-type", "application/problem+json").unwrap();
+        return tiny_http::Response::from_string("{\"title\": \"problem\"}")
+            .with_header(content_type)
+            .boxed();
+    }
+    if request.url() == "/redirect-long" {
+        // Longer than `http::Uri` allows.
+        let target = format!("/long/{}", "a".repeat(70_000));
+        let location = tiny_http::Header::from_bytes("location", target).unwrap();
+        return tiny_http::Response::empty(302)
+            .with_header(location)
+            .boxed();
+    }
+    if request.url() == "/ndjson" {
+        return tiny_http::Response::from_string("{\"i\": 0}\n{\"i\": 1}\n\n2\n").boxed();
+    }
+    if request.url() == "/lines" {
+        return tiny_http::Response::from_string("first\nsecond\r\n\nlast").boxed();
+    }
+
+    let out = serde_json::to_vec(&serde_json::json!({
+        "url": request.url(),
+    }))
+    .unwrap();
+
+    tiny_http::Response::from_data(out)
+        .with_status_code(200)
+        .boxed()
+}
+
+/// Timeouts must be classified the same way by every executor.
+fn assert_timeout(err: &HttpError) {
+    assert!(err.is_timeout(), "expected a timeout error, got: {err}");
+    assert!(
+        err.to_string().starts_with("request timed out"),
+        "unexpected timeout message: {err}"
+    );
 }
 
 pub async fn test_async_executor<E>(exec: E)
@@ -35,8 +494,9 @@ where
         Future<Output = Result<Vec<u8>, HttpError>> + Send + 'static,
     <E::ResponseBody as Respond>::Chunks:
         Stream<Item = Result<Vec<u8>, HttpError>> + Send + 'static,
+    <E::ResponseBody as Respond>::Reader: futures::io::AsyncRead + Send + 'static,
     E::Output: Future<Output = Result<Response<E::ResponseBody>, HttpError>> + Send + 'static,
-    E: Clone,
+    E: Clone + Send + Sync + 'static,
 {
     let server = start_test_server();
 
@@ -44,6 +504,8 @@ where
 
     let url = format!("http://{TEST_URL}/");
 
+    let stats_before = client.pool_stats();
+
     let res = client
         .get(&url)
         .send()
@@ -51,34 +513,1837 @@ where
         .unwrap()
         .error_for_status()
         .unwrap();
-    assert_eq!(res.uri.unwrap_or_default().to_string(), url);
+    assert_eq!(res.reason(), Some("OK"));
+    assert_eq!(res.uri.clone().unwrap_or_default().to_string(), url);
 
-    let mut chunks = Box::pin(res.body.into_chunks());
+    let mut chunks = res.chunks_async();
     let mut all = Vec::new();
     while let Some(res) = chunks.next().await {
         all.extend(res.unwrap());
     }
     serde_json::from_slice::<serde_json::Value>(&all).unwrap();
+    drop(chunks);
 
-    // FIXME: cookie tests
+    if let Some(before) = stats_before {
+        assert!(before.hosts.is_empty());
+        let stats = client.pool_stats().unwrap();
+        let host = stats.hosts[TEST_URL];
+        assert_eq!(host.in_use, 0);
+        assert_eq!(host.idle, 1);
+    }
 
-    server.unblock();
-}
+    // Abandoned responses don't break later requests.
+    let res = client.get(format!("{url}large")).send().await.unwrap();
+    res.abort();
+    let mut chunks = client
+        .get(format!("{url}large"))
+        .send()
+        .await
+        .unwrap()
+        .chunks_async();
+    chunks.next().await.unwrap().unwrap();
+    drop(chunks);
 
-pub fn test_sync_executor<E>(exec: E)
-where
-    E: HttpExecutor<Output = Result<crate::Response<crate::sync::GenericResponseBody>, HttpError>>,
-{
-    let server = start_test_server();
+    let res = client.get(format!("{url}lines")).send().await.unwrap();
+    assert_eq!(res.content_length(), Some(19));
+    res.abort();
 
-    let client = crate::Client::new(exec);
+    // Large responses are sent chunked, without a `Content-Length`.
+    let res = client.get(format!("{url}large")).send().await.unwrap();
+    let mut progress = Box::pin(res.into_chunks_with_progress());
+    let mut read = 0;
+    while let Some((chunk, total)) = progress.next().await.transpose().unwrap() {
+        read += chunk.len() as u64;
+        assert_eq!(total, read);
+    }
+    assert_eq!(read, 4 * 1024 * 1024);
 
-    let url = format!("http://{TEST_URL}/");
+    #[cfg(feature = "bytes")]
+    {
+        let bytes = client
+            .get(format!("{url}large"))
+            .send()
+            .await
+            .unwrap()
+            .bytes_buf_async()
+            .await
+            .unwrap();
+        assert_eq!(bytes.len(), 4 * 1024 * 1024);
+        assert!(bytes.iter().all(|b| *b == b'a'));
 
-    let res = client.get(&url).send().unwrap().error_for_status().unwrap();
-    assert_eq!(res.uri.clone().unwrap_or_default().to_string(), url);
+        let chunks = client
+            .get(format!("{url}large"))
+            .send()
+            .await
+            .unwrap()
+            .into_bytes_chunks();
+        let read = chunks
+            .map(|chunk| chunk.unwrap().len())
+            .fold(0, |read, len| async move { read + len })
+            .await;
+        assert_eq!(read, 4 * 1024 * 1024);
+    }
 
-    res.json_sync::<serde_json::Value>().unwrap();
+    // Cancel while the server is still waiting to respond.
+    let token = crate::cancel::CancelToken::new();
+    let request = client
+        .get(format!("{url}slow"))
+        .with_cancel(token.clone())
+        .send();
+    let (result, ()) = futures::join!(request, async { token.cancel() });
+    let err = result.err().unwrap();
+    assert!(err.is_cancelled());
+    assert_eq!(err.to_string(), "request cancelled");
+
+    let status = client
+        .status_async(Method::GET, format!("{url}status/204"))
+        .await
+        .unwrap();
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let lines = client
+        .get(format!("{url}lines"))
+        .send()
+        .await
+        .unwrap()
+        .lines_async()
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(lines, vec!["first", "second", "", "last"]);
+
+    let mut reader = Box::pin(
+        client
+            .get(format!("{url}lines"))
+            .send()
+            .await
+            .unwrap()
+            .body
+            .reader(),
+    );
+    let mut text = String::new();
+    futures::AsyncReadExt::read_to_string(&mut reader, &mut text)
+        .await
+        .unwrap();
+    assert_eq!(text, "first\nsecond\r\n\nlast");
+
+    let mut reader = client
+        .get(format!("{url}lines"))
+        .send()
+        .await
+        .unwrap()
+        .into_async_buf_read();
+    let mut line = String::new();
+    futures::AsyncBufReadExt::read_line(&mut reader, &mut line)
+        .await
+        .unwrap();
+    assert_eq!(line, "first\n");
+    let mut rest = Vec::new();
+    futures::AsyncReadExt::read_to_end(&mut reader, &mut rest)
+        .await
+        .unwrap();
+    assert_eq!(rest, b"second\r\n\nlast");
+
+    let res = Response::<crate::AsyncEmptyResponseBody>::default();
+    assert!(res.bytes_async().await.unwrap().is_empty());
+
+    let value = client
+        .post(format!("{url}form"))
+        .body_async_read(futures::io::Cursor::new(b"streamed".to_vec()))
+        .send()
+        .await
+        .unwrap()
+        .json_async::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(value["body"], "streamed");
+
+    // The timeout also covers a body that stalls after the head.
+    let res = client
+        .get(format!("{url}slow-body"))
+        .timeout(std::time::Duration::from_millis(100))
+        .send()
+        .await
+        .unwrap();
+    let err = res.bytes_async().await.err().unwrap();
+    assert_timeout(&err);
+
+    let err = client
+        .get(format!("{url}slow"))
+        .timeout(std::time::Duration::from_millis(50))
+        .send()
+        .await
+        .err()
+        .unwrap();
+    assert_timeout(&err);
+    let err = client
+        .get(&url)
+        .deadline(std::time::Instant::now())
+        .send()
+        .await
+        .err()
+        .unwrap();
+    assert_timeout(&err);
+
+    let (mut sender, builder) = client.post(format!("{url}form")).body_channel();
+    let produce = async move {
+        for chunk in ["log ", "lines"] {
+            futures::SinkExt::send(&mut sender, chunk.as_bytes().to_vec())
+                .await
+                .unwrap();
+        }
+    };
+    let (res, ()) = futures::join!(builder.send(), produce);
+    let value = res
+        .unwrap()
+        .json_async::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(value["body"], "log lines");
+
+    // The interim `100 Continue` response is skipped.
+    let value = client
+        .post(format!("{url}form"))
+        .expect_continue()
+        .json_value(serde_json::json!("data"))
+        .send()
+        .await
+        .unwrap()
+        .error_for_status()
+        .unwrap()
+        .json_async::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(value["body"], "\"data\"");
+
+    let items = client
+        .get(format!("{url}json-array"))
+        .send()
+        .await
+        .unwrap()
+        .json_stream_async::<serde_json::Value>()
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(items.len(), 2000);
+    assert_eq!(items[1999], serde_json::json!({"i": 1999}));
+
+    let items = client
+        .get(format!("{url}ndjson"))
+        .send()
+        .await
+        .unwrap()
+        .json_stream_async::<serde_json::Value>()
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        items,
+        vec![
+            serde_json::json!({"i": 0}),
+            serde_json::json!({"i": 1}),
+            serde_json::json!(2)
+        ]
+    );
+
+    let mut items = Box::pin(
+        client
+            .get(format!("{url}lines"))
+            .send()
+            .await
+            .unwrap()
+            .json_stream_async::<serde_json::Value>(),
+    );
+    assert!(items.next().await.unwrap().is_err());
+
+    // The executor works behind the dynamic client, which needs a reader.
+    let dyn_client = crate::Client::new_dyn_async(client.executor_cloned());
+    let res = dyn_client.get(format!("{url}lines")).send().await.unwrap();
+    assert_eq!(res.bytes_async().await.unwrap(), b"first\nsecond\r\n\nlast");
+    let res = dyn_client
+        .get(format!("{url}created"))
+        .send()
+        .await
+        .unwrap()
+        .buffered_async()
+        .await
+        .unwrap();
+    assert_eq!(res.status, StatusCode::CREATED);
+    assert_eq!(res.header_str("location"), Some("/items/1"));
+    assert_eq!(res.body, b"created");
+
+    // Draining reads the body to the end.
+    let res = client.get(format!("{url}lines")).send().await.unwrap();
+    assert_eq!(res.drain_async().await.unwrap(), 19);
+    let res = dyn_client
+        .get(format!("{url}created"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.drain_async().await.unwrap(), 7);
+    let res = client.get(format!("{url}lines")).send().await.unwrap();
+    assert_eq!(res.trailers_async().await.unwrap(), None);
+
+    // HEAD responses have no body, even with a `Content-Length`.
+    for path in ["large", "gzip"] {
+        let res = client.head(format!("{url}{path}")).send().await.unwrap();
+        assert_eq!(res.status, StatusCode::OK);
+        assert!(res.bytes_async().await.unwrap().is_empty());
+    }
+
+    let err = client
+        .get(format!("{url}invalid"))
+        .send()
+        .await
+        .unwrap()
+        .error_for_status_with_body_async(13)
+        .await
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.to_string(),
+        "Request failed with status 400 Bad Request: invalid field"
+    );
+
+    // Nothing listens on port 1.
+    let err = client
+        .get("http://127.0.0.1:1/")
+        .send()
+        .await
+        .err()
+        .unwrap();
+    assert!(err.is_connect());
+
+    // The observer times the whole response future.
+    let metrics = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let observed_client = client.clone().with_observer({
+        let metrics = metrics.clone();
+        Arc::new(move |m: &crate::metrics::RequestMetrics| metrics.lock().unwrap().push(m.clone()))
+    });
+    let res = observed_client
+        .get(format!("{url}slow"))
+        .send()
+        .await
+        .unwrap();
+    assert!(res.elapsed().unwrap() >= std::time::Duration::from_millis(300));
+    assert!(client.get(&url).send().await.unwrap().elapsed().is_none());
+    observed_client
+        .get("http://127.0.0.1:1/")
+        .send()
+        .await
+        .err()
+        .unwrap();
+    {
+        let metrics = metrics.lock().unwrap();
+        assert_eq!(metrics.len(), 2);
+        assert_eq!(metrics[0].method, Method::GET);
+        assert_eq!(metrics[0].uri.path(), "/slow");
+        assert_eq!(metrics[0].status, Some(StatusCode::OK));
+        assert!(metrics[0].duration >= std::time::Duration::from_millis(300));
+        assert_eq!(metrics[1].status, None);
+    }
+    // Credentials in the URI are not reported to the observer.
+    #[cfg(feature = "base64")]
+    {
+        observed_client
+            .get(format!("http://user:secret@{TEST_URL}/authorization"))
+            .send()
+            .await
+            .unwrap();
+        let metrics = metrics.lock().unwrap();
+        assert_eq!(
+            metrics.last().unwrap().uri.to_string(),
+            format!("http://{TEST_URL}/authorization")
+        );
+    }
+
+    #[cfg(feature = "sse")]
+    {
+        let events = client
+            .get(format!("{url}events"))
+            .send()
+            .await
+            .unwrap()
+            .events_async()
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            events,
+            vec![
+                crate::sse::Event {
+                    id: None,
+                    event: None,
+                    data: "first".to_string(),
+                    retry: Some(1000),
+                },
+                crate::sse::Event {
+                    id: Some("2".to_string()),
+                    event: Some("update".to_string()),
+                    data: "line1\nline2".to_string(),
+                    retry: None,
+                },
+            ]
+        );
+    }
+
+    #[cfg(feature = "compression")]
+    for encoding in ["gzip", "deflate", "br"] {
+        let res = client
+            .get(format!("{url}{encoding}"))
+            .no_decompress()
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(
+            res.header_str(http::header::CONTENT_ENCODING),
+            Some(encoding)
+        );
+        let bytes = res.bytes_decoded_async().await.unwrap();
+        let value = serde_json::from_slice::<serde_json::Value>(&bytes).unwrap();
+        assert_eq!(value["url"], format!("/{encoding}"));
+    }
+
+    // Only `Content-Encoding` is decoded: the transport removes the
+    // chunked framing, and plain chunked bodies pass through untouched.
+    let res = client.get(format!("{url}chunked")).send().await.unwrap();
+    assert_eq!(res.content_length(), None);
+    assert_eq!(
+        res.bytes_async().await.unwrap(),
+        CHUNKED_BODY.repeat(1000).as_bytes()
+    );
+    #[cfg(feature = "compression")]
+    {
+        let res = client
+            .get(format!("{url}gzip-chunked"))
+            .send()
+            .await
+            .unwrap();
+        assert!(!res.headers.contains_key(http::header::CONTENT_ENCODING));
+        assert_eq!(
+            res.bytes_async().await.unwrap(),
+            CHUNKED_BODY.repeat(1000).as_bytes()
+        );
+    }
+
+    #[cfg(feature = "compression")]
+    for encoding in ["gzip", "deflate", "br"] {
+        let res = client.get(format!("{url}{encoding}")).send().await.unwrap();
+        assert!(!res.headers.contains_key(http::header::CONTENT_ENCODING));
+        let value = res.json_async::<serde_json::Value>().await.unwrap();
+        assert_eq!(value["url"], format!("/{encoding}"));
+        assert_eq!(
+            value["accept_encoding"],
+            crate::compression::ACCEPT_ENCODING
+        );
+    }
+
+    #[cfg(feature = "multipart")]
+    {
+        use futures::TryStreamExt;
+
+        let res = client.get(format!("{url}multipart")).send().await.unwrap();
+        let parts = res.multipart_async().try_collect::<Vec<_>>().await.unwrap();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].headers["content-range"], "bytes 0-2/10");
+        assert_eq!(parts[0].body, b"abc");
+        assert!(parts[1].headers.is_empty());
+        assert_eq!(parts[1].body, b"line\r\n--other");
+
+        let res = client.get(format!("{url}lines")).send().await.unwrap();
+        let mut parts = Box::pin(res.multipart_async());
+        assert!(parts.next().await.unwrap().is_err());
+    }
+
+    let has_host = client
+        .get(format!("{url}headers"))
+        .send()
+        .await
+        .unwrap()
+        .with_json_bytes_async(|bytes| {
+            let names = serde_json::from_slice::<Vec<&str>>(bytes)?;
+            Ok(names.contains(&"host"))
+        })
+        .await
+        .unwrap();
+    assert!(has_host);
+
+    let value = client
+        .get(format!("{url}prefixed-json"))
+        .send()
+        .await
+        .unwrap()
+        .json_with_prefix_strip_async::<serde_json::Value>(")]}',\n")
+        .await
+        .unwrap();
+    assert_eq!(value, serde_json::json!({"a": 1}));
+
+    let res = client.get(format!("{url}lines")).send().await.unwrap();
+    assert!(res
+        .json_with_prefix_strip_async::<serde_json::Value>(")]}',\n")
+        .await
+        .is_err());
+
+    let reset_server = start_reset_server();
+    let policy = crate::retry::RetryPolicy::new(1).retry_on_reset(true);
+    let retry_client = crate::Client::new(crate::AsyncRetryExecutor::new(
+        client.executor_cloned(),
+        policy,
+    ));
+    let body = retry_client
+        .get(format!("http://{RESET_URL}/"))
+        .send()
+        .await
+        .unwrap()
+        .bytes_async()
+        .await
+        .unwrap();
+    assert_eq!(body, b"ok");
+    reset_server.join().unwrap();
+
+    #[cfg(feature = "base64")]
+    {
+        let value = client
+            .get(format!("http://user:p%40ss@{TEST_URL}/authorization"))
+            .send()
+            .await
+            .unwrap()
+            .json_async::<serde_json::Value>()
+            .await
+            .unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({"authorization": "Basic dXNlcjpwQHNz", "host": TEST_URL})
+        );
+    }
+
+    let value = client
+        .get(format!("{url}problem-json"))
+        .send()
+        .await
+        .unwrap()
+        .json_if_json_async::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(value["title"], "problem");
+    let err = client
+        .get(format!("{url}created"))
+        .send()
+        .await
+        .unwrap()
+        .json_if_json_async::<serde_json::Value>()
+        .await
+        .unwrap_err();
+    assert!(err.is_unexpected_content_type());
+    assert!(err.to_string().contains("text/plain; charset=utf-8"));
+
+    let requests = (0..5)
+        .map(|index| client.get(format!("{url}batch/{index}")).build().unwrap())
+        .chain(Some(client.get("http://127.0.0.1:1/").build().unwrap()));
+    // Concurrent batches are checked in `test_async_executor_strict`.
+    let results = client.send_all(requests, 1).collect::<Vec<_>>().await;
+    assert_eq!(results.len(), 6);
+    assert_eq!(results.iter().filter(|res| res.is_err()).count(), 1);
+    let mut paths = Vec::new();
+    for res in results.into_iter().filter_map(|res| res.ok()) {
+        let value = res.json_async::<serde_json::Value>().await.unwrap();
+        paths.push(value["url"].as_str().unwrap().to_string());
+    }
+    paths.sort();
+    assert_eq!(
+        paths,
+        ["/batch/0", "/batch/1", "/batch/2", "/batch/3", "/batch/4"]
+    );
+
+    let strict = client.clone().with_strict_mode(true);
+    let err = strict
+        .get(format!("{url}method"))
+        .header(http::header::CONTENT_LENGTH, "3")
+        .send()
+        .await
+        .err()
+        .unwrap();
+    assert!(err.is_invalid_request());
+    assert!(err
+        .to_string()
+        .contains("GET requests must not have a body"));
+    let value = strict
+        .get(format!("{url}method"))
+        .send()
+        .await
+        .unwrap()
+        .json_async::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(value["method"], "GET");
+
+    let limited = client.clone().with_max_request_body_size(Some(4));
+    let value = limited
+        .post(format!("{url}form"))
+        .json_value(serde_json::json!("ok"))
+        .send()
+        .await
+        .unwrap()
+        .json_async::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(value["body"], "\"ok\"");
+    let err = limited
+        .post(format!("{url}form"))
+        .json_value(serde_json::json!("too long"))
+        .send()
+        .await
+        .err()
+        .unwrap();
+    assert!(err.is_request_too_large());
+    assert!(err.to_string().contains("limit of 4 bytes"));
+    let err = limited
+        .post(format!("{url}form"))
+        .header(http::header::CONTENT_LENGTH, "5")
+        .send()
+        .await
+        .err()
+        .unwrap();
+    assert!(err.is_request_too_large());
+    // Streamed bodies fail while they are sent.
+    let res = limited
+        .post(format!("{url}form"))
+        .body_async_read(futures::io::Cursor::new(b"streamed".to_vec()))
+        .send()
+        .await;
+    assert!(res.is_err());
+
+    let ua_client = client
+        .clone()
+        .with_user_agent(Some(HeaderValue::from_static(crate::DEFAULT_USER_AGENT)));
+    let user_agent = ua_client
+        .get(format!("{url}user-agent"))
+        .send()
+        .await
+        .unwrap()
+        .bytes_async()
+        .await
+        .unwrap();
+    assert_eq!(user_agent, crate::DEFAULT_USER_AGENT.as_bytes());
+    let user_agent = ua_client
+        .get(format!("{url}user-agent"))
+        .header(http::header::USER_AGENT, "custom")
+        .send()
+        .await
+        .unwrap()
+        .bytes_async()
+        .await
+        .unwrap();
+    assert_eq!(user_agent, b"custom");
+    let user_agent = ua_client
+        .with_user_agent(None)
+        .get(format!("{url}user-agent"))
+        .send()
+        .await
+        .unwrap()
+        .bytes_async()
+        .await
+        .unwrap();
+    assert_ne!(user_agent, crate::DEFAULT_USER_AGENT.as_bytes());
+
+    // Mutating requests without a body are sent with `Content-Length: 0`.
+    for method in [Method::POST, Method::PUT, Method::PATCH] {
+        let value = client
+            .request(method.clone(), format!("{url}method"))
+            .send()
+            .await
+            .unwrap()
+            .json_async::<serde_json::Value>()
+            .await
+            .unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({"method": method.as_str(), "content_length": "0"})
+        );
+    }
+    for (builder, method) in [
+        (client.options(format!("{url}method")), "OPTIONS"),
+        (client.trace(format!("{url}method")), "TRACE"),
+    ] {
+        let value = builder
+            .send()
+            .await
+            .unwrap()
+            .json_async::<serde_json::Value>()
+            .await
+            .unwrap();
+        assert_eq!(value["method"], method);
+    }
+
+    let cookies = client
+        .get(format!("{url}cookies"))
+        .cookie("a", "1")
+        .cookie("b", "2")
+        .send()
+        .await
+        .unwrap()
+        .json_async::<Vec<String>>()
+        .await
+        .unwrap();
+    assert_eq!(cookies, vec!["a=1; b=2"]);
+
+    #[cfg(feature = "cookies")]
+    {
+        let client = crate::Client::new_with_cookie_jar(client.executor_cloned());
+        let (client, url) = (&client, &url);
+        let sent_cookies = || async move {
+            let header = client
+                .get(format!("{url}cookies"))
+                .send()
+                .await
+                .unwrap()
+                .json_async::<Vec<String>>()
+                .await
+                .unwrap();
+            let mut cookies = header
+                .iter()
+                .flat_map(|h| h.split("; "))
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>();
+            cookies.sort();
+            cookies
+        };
+
+        // Cookies are stored from error responses too.
+        let res = client.get(format!("{url}login")).send().await.unwrap();
+        assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+        assert_eq!(sent_cookies().await, vec!["session=abc"]);
+
+        // Cookies set on the request are merged with the jar, and win on
+        // conflicts.
+        let cookies = client
+            .get(format!("{url}cookies"))
+            .cookie("session", "override")
+            .cookie("extra", "1")
+            .send()
+            .await
+            .unwrap()
+            .json_async::<Vec<String>>()
+            .await
+            .unwrap();
+        assert_eq!(cookies, vec!["session=override; extra=1"]);
+
+        // Expired, foreign-domain, malformed and other-path cookies are not
+        // sent.
+        client
+            .get(format!("{url}set-cookies"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(sent_cookies().await, vec!["a=1", "session=abc", "temp=1"]);
+    }
+
+    // A custom jar receives the parsed cookies and provides its own.
+    #[cfg(feature = "cookies")]
+    {
+        #[derive(Default)]
+        struct Jar(std::sync::Mutex<Vec<String>>);
+
+        impl crate::cookies::CookieJar for Jar {
+            fn store_response_cookies(
+                &self,
+                cookies: Vec<cookie::Cookie<'static>>,
+                _url: &url::Url,
+            ) {
+                let mut names = self.0.lock().unwrap();
+                names.extend(cookies.iter().map(|c| c.name().to_string()));
+            }
+
+            fn get_request_values(&self, _url: &url::Url) -> Vec<(String, String)> {
+                vec![("tenant".to_string(), "7".to_string())]
+            }
+        }
+
+        let jar = std::sync::Arc::new(Jar::default());
+        let client =
+            crate::Client::new_with_custom_cookie_jar(client.executor_cloned(), jar.clone());
+        client
+            .get(format!("{url}set-cookies"))
+            .send()
+            .await
+            .unwrap();
+        assert!(jar.0.lock().unwrap().ends_with(&["sub".to_string()]));
+        let cookies = client
+            .get(format!("{url}cookies"))
+            .send()
+            .await
+            .unwrap()
+            .json_async::<Vec<String>>()
+            .await
+            .unwrap();
+        assert_eq!(cookies, vec!["tenant=7"]);
+    }
+
+    server.stop();
+}
+
+/// Checks that depend on the wire format produced by the backend, which not
+/// every executor can pass: non-standard status codes and header order, and
+/// concurrent requests to the single-threaded test server.
+pub async fn test_async_executor_strict<E>(exec: E)
+where
+    E: HttpExecutor,
+    E::ResponseBody: Respond + Send + 'static,
+    <E::ResponseBody as Respond>::BytesOutput:
+        Future<Output = Result<Vec<u8>, HttpError>> + Send + 'static,
+    <E::ResponseBody as Respond>::Chunks:
+        Stream<Item = Result<Vec<u8>, HttpError>> + Send + 'static,
+    E::Output: Future<Output = Result<Response<E::ResponseBody>, HttpError>> + Send + 'static,
+{
+    let server = start_test_server();
+    let client = crate::Client::new(exec);
+    let url = format!("http://{TEST_URL}/");
+
+    let requests = (0..5).map(|index| client.get(format!("{url}batch/{index}")).build().unwrap());
+    let results = client.send_all(requests, 3).collect::<Vec<_>>().await;
+    assert_eq!(results.len(), 5);
+    assert!(results.iter().all(|res| res.is_ok()));
+
+    let res = client.get(format!("{url}status/599")).send().await.unwrap();
+    assert_eq!(res.status.as_u16(), 599);
+    let err = res.error_for_status().err().unwrap();
+    assert_eq!(err.to_string(), "Request failed with status 599");
+    assert!(err.is_status());
+
+    let names = client
+        .get(format!("{url}headers"))
+        .header("x-a", "a")
+        .header("x-b", "b")
+        .header("x-c", "c")
+        .header_order(vec![
+            HeaderName::from_static("x-c"),
+            HeaderName::from_static("x-a"),
+        ])
+        .send()
+        .await
+        .unwrap()
+        .json_async::<Vec<String>>()
+        .await
+        .unwrap();
+    let names = names
+        .into_iter()
+        .filter(|n| n.starts_with("x-"))
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["x-c", "x-a", "x-b"]);
+
+    // Repeated names in the order don't duplicate the header.
+    let names = client
+        .get(format!("{url}headers"))
+        .header("x-a", "a")
+        .header("x-b", "b")
+        .header("x-c", "c")
+        .header_order(vec![
+            HeaderName::from_static("x-c"),
+            HeaderName::from_static("x-a"),
+            HeaderName::from_static("x-c"),
+        ])
+        .send()
+        .await
+        .unwrap()
+        .json_async::<Vec<String>>()
+        .await
+        .unwrap();
+    let names = names
+        .into_iter()
+        .filter(|n| n.starts_with("x-"))
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["x-c", "x-a", "x-b"]);
+
+    server.stop();
+}
+
+/// Test an executor that supports [`crate::RequestPre::connect_to`].
+pub async fn test_async_connect_to<E>(exec: E)
+where
+    E: HttpExecutor,
+    E::ResponseBody: Respond + Send + 'static,
+    <E::ResponseBody as Respond>::BytesOutput:
+        Future<Output = Result<Vec<u8>, HttpError>> + Send + 'static,
+    <E::ResponseBody as Respond>::Chunks:
+        Stream<Item = Result<Vec<u8>, HttpError>> + Send + 'static,
+    E::Output: Future<Output = Result<Response<E::ResponseBody>, HttpError>> + Send + 'static,
+    E: Clone,
+{
+    let server = start_test_server();
+    let client = crate::Client::new(exec);
+    let addr = TEST_URL.parse::<std::net::SocketAddr>().unwrap();
+
+    // The host doesn't resolve, so the request can only succeed by
+    // connecting to the override.
+    let url = "http://service.invalid:8080/host";
+    let res = client.get(url).connect_to(addr).send().await.unwrap();
+    assert_eq!(res.uri.as_ref().unwrap().to_string(), url);
+    let host = res.bytes_async().await.unwrap();
+    assert_eq!(host, b"service.invalid:8080");
+
+    let overridden = client.with_host_overrides(
+        [("Service.Invalid".to_string(), addr)]
+            .into_iter()
+            .collect(),
+    );
+    let res = overridden.get(url).send().await.unwrap();
+    assert_eq!(res.uri.as_ref().unwrap().to_string(), url);
+    let host = res.bytes_async().await.unwrap();
+    assert_eq!(host, b"service.invalid:8080");
+
+    server.stop();
+}
+
+/// Test an executor that uses the test server as a proxy, with the
+/// credentials `user:pass`.
+pub async fn test_async_proxy_executor<E>(exec: E)
+where
+    E: HttpExecutor,
+    E::ResponseBody: Respond + Send + 'static,
+    <E::ResponseBody as Respond>::BytesOutput:
+        Future<Output = Result<Vec<u8>, HttpError>> + Send + 'static,
+    <E::ResponseBody as Respond>::Chunks:
+        Stream<Item = Result<Vec<u8>, HttpError>> + Send + 'static,
+    E::Output: Future<Output = Result<Response<E::ResponseBody>, HttpError>> + Send + 'static,
+{
+    let server = start_test_server();
+    let client = crate::Client::new(exec);
+
+    let value = client
+        .get("http://anyhttp.invalid/proxied")
+        .send()
+        .await
+        .unwrap()
+        .json_async::<serde_json::Value>()
+        .await
+        .unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!({
+            "url": "http://anyhttp.invalid/proxied",
+            "proxy_authorization": "Basic dXNlcjpwYXNz",
+        })
+    );
+
+    server.stop();
+}
+
+pub fn test_sync_executor<E>(exec: E)
+where
+    E: HttpExecutor<
+        ResponseBody = crate::sync::GenericResponseBody,
+        Output = Result<crate::Response<crate::sync::GenericResponseBody>, HttpError>,
+    >,
+    E: Clone + 'static,
+{
+    let server = start_test_server();
+
+    let client = crate::Client::new(exec);
+
+    let url = format!("http://{TEST_URL}/");
+
+    let res = client.get(&url).send().unwrap().error_for_status().unwrap();
+    assert_eq!(res.uri.clone().unwrap_or_default().to_string(), url);
+
+    res.json_sync::<serde_json::Value>().unwrap();
+
+    let request = client.get(format!("{url}created")).build().unwrap().request;
+    let res = crate::send_once(&client.executor_cloned(), request).unwrap();
+    assert_eq!(res.status, StatusCode::CREATED);
+
+    // Both request types can be sent directly.
+    let body = || {
+        client
+            .executor_cloned()
+            .request_body_from_generic(crate::RequestBody::Empty)
+    };
+    let req = http::Request::get(format!("{url}status/204"))
+        .body(body())
+        .unwrap();
+    assert_eq!(client.send(req).unwrap().status, StatusCode::NO_CONTENT);
+    let mut req = crate::Request::new(body());
+    req.uri = format!("{url}status/204").parse().unwrap();
+    assert_eq!(client.send(req).unwrap().status, StatusCode::NO_CONTENT);
+
+    let value = client
+        .get(format!("{url}users?x=1"))
+        .path_segment("a b/c")
+        .send()
+        .unwrap()
+        .json_sync::<serde_json::Value>()
+        .unwrap();
+    assert_eq!(value["url"], "/users/a%20b%2Fc?x=1");
+    let builder = client.get(&url).path_segment("..");
+    assert!(builder.is_err());
+    assert!(builder.error().unwrap().is_invalid_request());
+    assert!(!client.get(&url).is_err());
+    let err = client.get(&url).path_segment("..").send().err().unwrap();
+    assert!(err.is_invalid_request());
+
+    let res = Response::<crate::EmptyResponseBody>::default();
+    assert!(res.bytes_sync().unwrap().is_empty());
+
+    // Libraries can accept a type-erased client.
+    let dyn_exec = crate::sync::into_dyn_executor(client.executor_cloned());
+    let dyn_client = crate::sync::DynClient::from(dyn_exec);
+    let res = dyn_client.get(format!("{url}lines")).send().unwrap();
+    assert_eq!(res.bytes_sync().unwrap(), b"first\nsecond\r\n\nlast");
+    let res = dyn_client
+        .get(format!("{url}created"))
+        .send()
+        .unwrap()
+        .buffered_sync()
+        .unwrap();
+    assert_eq!(res.status, StatusCode::CREATED);
+    assert_eq!(res.header_str("location"), Some("/items/1"));
+    assert_eq!(res.body, b"created");
+    let res = client.get(format!("{url}lines")).send().unwrap();
+    assert_eq!(res.drain_sync().unwrap(), 19);
+    let res = client.get(format!("{url}lines")).send().unwrap();
+    let body = res
+        .chunks_sync()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap()
+        .concat();
+    assert_eq!(body, b"first\nsecond\r\n\nlast");
+    let res = dyn_client.get(format!("{url}created")).send().unwrap();
+    assert_eq!(res.drain_sync().unwrap(), 7);
+    let req = http::Request::post(format!("{url}form"))
+        .body(crate::RequestBody::from("generic"))
+        .unwrap();
+    let value = dyn_client
+        .send(req)
+        .unwrap()
+        .json_sync::<serde_json::Value>()
+        .unwrap();
+    assert_eq!(value["body"], "generic");
+    let res = client
+        .get(format!("{url}headers"))
+        .send()
+        .unwrap()
+        .buffered_sync()
+        .unwrap();
+    let names = res.json::<Vec<&str>>().unwrap();
+    assert!(names.contains(&"host"));
+    assert!(res.json::<serde_json::Value>().unwrap().is_array());
+
+    let token = crate::cancel::CancelToken::new();
+    token.cancel();
+    let err = client.get(&url).with_cancel(token).send().err().unwrap();
+    assert!(err.is_cancelled());
+
+    let res = client.get(format!("{url}created")).send().unwrap();
+    assert_eq!(res.reason(), Some("Created"));
+    assert_eq!(res.header_str("location"), Some("/items/1"));
+    assert_eq!(res.header_str("x-missing"), None);
+    assert_eq!(res.header_all_joined("x-missing"), None);
+    assert_eq!(res.location().unwrap().path(), "/items/1");
+    #[cfg(feature = "mime")]
+    {
+        let content_type = res.content_type().unwrap();
+        assert_eq!(content_type.essence_str(), "text/plain");
+        assert_eq!(content_type.get_param(mime::CHARSET), Some(mime::UTF_8));
+    }
+
+    #[cfg(feature = "url")]
+    {
+        let parsed = format!("{url}created#fragment")
+            .parse::<url::Url>()
+            .unwrap();
+        let res = client.get(&parsed).send().unwrap();
+        assert_eq!(res.status, StatusCode::CREATED);
+        assert_eq!(res.uri.unwrap().to_string(), format!("{url}created"));
+        let res = crate::RequestBuilder::new(client.clone())
+            .url(&parsed)
+            .send()
+            .unwrap();
+        assert_eq!(res.status, StatusCode::CREATED);
+        let res = client.request(Method::GET, parsed).send().unwrap();
+        assert_eq!(res.status, StatusCode::CREATED);
+    }
+
+    let value = client
+        .get(format!("{url}problem-json"))
+        .send()
+        .unwrap()
+        .json_if_json_sync::<serde_json::Value>()
+        .unwrap();
+    assert_eq!(value["title"], "problem");
+    let err = client
+        .get(format!("{url}created"))
+        .send()
+        .unwrap()
+        .json_if_json_sync::<serde_json::Value>()
+        .unwrap_err();
+    assert!(err.is_unexpected_content_type());
+
+    // Abandoned responses don't break later requests.
+    let res = client.get(format!("{url}large")).send().unwrap();
+    res.abort();
+    let mut reader = client
+        .get(format!("{url}large"))
+        .send()
+        .unwrap()
+        .body
+        .reader();
+    std::io::Read::read_exact(&mut reader, &mut [0; 1024]).unwrap();
+    drop(reader);
+
+    // HEAD responses have no body, even with a `Content-Length`.
+    for path in ["large", "gzip"] {
+        let res = client.head(format!("{url}{path}")).send().unwrap();
+        assert_eq!(res.status, StatusCode::OK);
+        assert!(res.bytes_sync().unwrap().is_empty());
+    }
+
+    let status = client
+        .status_sync(Method::GET, format!("{url}status/204"))
+        .unwrap();
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let lines = client
+        .get(format!("{url}lines"))
+        .send()
+        .unwrap()
+        .lines_sync()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(lines, vec!["first", "second", "", "last"]);
+
+    let mut reader = client
+        .get(format!("{url}lines"))
+        .send()
+        .unwrap()
+        .into_buf_read();
+    let mut line = String::new();
+    std::io::BufRead::read_line(&mut reader, &mut line).unwrap();
+    assert_eq!(line, "first\n");
+    let mut rest = Vec::new();
+    std::io::Read::read_to_end(&mut reader, &mut rest).unwrap();
+    assert_eq!(rest, b"second\r\n\nlast");
+
+    let err = client
+        .get(format!("{url}invalid"))
+        .send()
+        .unwrap()
+        .error_for_status_with_body_sync(13)
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.to_string(),
+        "Request failed with status 400 Bad Request: invalid field"
+    );
+    client
+        .get(&url)
+        .send()
+        .unwrap()
+        .error_for_status_with_body_sync(13)
+        .unwrap()
+        .json_sync::<serde_json::Value>()
+        .unwrap();
+
+    let res = client.get(format!("{url}status/599")).send().unwrap();
+    assert_eq!(res.status.as_u16(), 599);
+    assert!(res.error_for_status_ref().unwrap_err().is_status());
+    let err = res.error_for_status().err().unwrap();
+    assert_eq!(err.to_string(), "Request failed with status 599");
+    assert!(err.is_status());
+
+    let is_ok = |status: StatusCode| status.is_success() || status == StatusCode::NOT_MODIFIED;
+    let res = client.get(format!("{url}status/304")).send().unwrap();
+    assert_eq!(res.error_for_status_if(is_ok).unwrap().status, 304);
+    let res = client.get(format!("{url}status/404")).send().unwrap();
+    assert!(res.error_for_status_if(is_ok).err().unwrap().is_status());
+
+    // Nothing listens on port 1.
+    let err = client.get("http://127.0.0.1:1/").send().err().unwrap();
+    assert!(err.is_connect());
+
+    let cookies = client
+        .get(format!("{url}cookies"))
+        .cookie("a", "1")
+        .cookie("b", "2")
+        .send()
+        .unwrap()
+        .json_sync::<Vec<String>>()
+        .unwrap();
+    assert_eq!(cookies, vec!["a=1; b=2"]);
+
+    // A custom jar receives the parsed cookies and provides its own.
+    #[cfg(feature = "cookies")]
+    {
+        #[derive(Default)]
+        struct Jar(std::sync::Mutex<Vec<String>>);
+
+        impl crate::cookies::CookieJar for Jar {
+            fn store_response_cookies(
+                &self,
+                cookies: Vec<cookie::Cookie<'static>>,
+                _url: &url::Url,
+            ) {
+                let mut names = self.0.lock().unwrap();
+                names.extend(cookies.iter().map(|c| c.name().to_string()));
+            }
+
+            fn get_request_values(&self, _url: &url::Url) -> Vec<(String, String)> {
+                vec![("tenant".to_string(), "7".to_string())]
+            }
+        }
+
+        let jar = std::sync::Arc::new(Jar::default());
+        let client =
+            crate::Client::new_with_custom_cookie_jar(client.executor_cloned(), jar.clone());
+        client.get(format!("{url}set-cookies")).send().unwrap();
+        // Some executors only keep the last of repeated headers.
+        assert!(jar.0.lock().unwrap().ends_with(&["sub".to_string()]));
+        let cookies = client
+            .get(format!("{url}cookies"))
+            .send()
+            .unwrap()
+            .json_sync::<Vec<String>>()
+            .unwrap();
+        assert_eq!(cookies, vec!["tenant=7"]);
+    }
+
+    let parse =
+        |raw: &str| -> Result<serde_json::Value, HttpError> { Ok(serde_json::from_str(raw)?) };
+    let err = parse("{").unwrap_err();
+    assert_eq!(err.json_error_location(), Some((1, 1)));
+    assert!(err
+        .to_string()
+        .starts_with("could not deserialize JSON response"));
+    let io = || -> Result<(), HttpError> { Err(std::io::Error::other("boom"))? };
+    assert_eq!(io().unwrap_err().to_string(), "io error: boom");
+    let err = io().unwrap_err();
+    let cause = err.downcast_ref::<std::io::Error>().unwrap();
+    assert_eq!(cause.kind(), std::io::ErrorKind::Other);
+    assert!(err.downcast_ref::<http::Error>().is_none());
+
+    let body = crate::RequestBody::try_from(serde_json::json!({"a": [1, 2]})).unwrap();
+    assert!(matches!(body, crate::RequestBody::Bytes(b) if b == br#"{"a":[1,2]}"#));
+
+    let has_host = client
+        .get(format!("{url}headers"))
+        .send()
+        .unwrap()
+        .with_json_bytes_sync(|bytes| {
+            let names = serde_json::from_slice::<Vec<&str>>(bytes)?;
+            Ok(names.contains(&"host"))
+        })
+        .unwrap();
+    assert!(has_host);
+    let err = client
+        .get(format!("{url}lines"))
+        .send()
+        .unwrap()
+        .with_json_bytes_sync(|bytes| Ok(serde_json::from_slice::<Vec<&str>>(bytes)?.len()))
+        .err()
+        .unwrap();
+    assert!(err.json_error_location().is_some());
+
+    let value = client
+        .get(format!("{url}prefixed-json"))
+        .send()
+        .unwrap()
+        .json_with_prefix_strip_sync::<serde_json::Value>(")]}',\n")
+        .unwrap();
+    assert_eq!(value, serde_json::json!({"a": 1}));
+
+    let err = client
+        .get(format!("{url}lines"))
+        .send()
+        .unwrap()
+        .json_sync::<serde_json::Value>()
+        .err()
+        .unwrap();
+    assert_eq!(err.json_error_location(), Some((1, 2)));
+    assert!(err.to_string().ends_with("at line 1 column 2"));
+    assert!(err.to_string().contains("near `first second   last`"));
+
+    let body_lengths = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let logger = {
+        let body_lengths = body_lengths.clone();
+        move |req: &mut crate::middleware::MiddlewareRequest<'_>| {
+            body_lengths
+                .lock()
+                .unwrap()
+                .push(req.body_bytes().map(|b| b.len()));
+            Ok(())
+        }
+    };
+    struct Tag(&'static str);
+    let tagging_client =
+        client
+            .clone()
+            .with_middleware(|req: &mut crate::middleware::MiddlewareRequest<'_>| {
+                if let Some(tag) = req.extensions.get::<Tag>() {
+                    req.headers
+                        .insert("x-tag", crate::HeaderValue::from_static(tag.0));
+                }
+                Ok(())
+            });
+    let headers = tagging_client
+        .get(format!("{url}headers"))
+        .extension(Tag("tagged"))
+        .send()
+        .unwrap()
+        .json_sync::<Vec<String>>()
+        .unwrap();
+    assert!(headers.contains(&"x-tag".to_string()));
+
+    let mut builder = client.get(format!("{url}headers"));
+    for i in 0..3 {
+        builder.headers_mut().unwrap().insert(
+            HeaderName::try_from(format!("x-loop-{i}")).unwrap(),
+            crate::HeaderValue::from_static("1"),
+        );
+    }
+    *builder.method_mut().unwrap() = Method::POST;
+    let pre = builder.build().unwrap();
+    assert_eq!(pre.request.method, Method::POST);
+    let headers = client
+        .send_pre(pre)
+        .unwrap()
+        .json_sync::<Vec<String>>()
+        .unwrap();
+    assert!(headers.contains(&"x-loop-2".to_string()));
+
+    let signing_client = client.clone().with_signer(
+        |req: &mut crate::Request<()>, body: &[u8]| -> Result<(), HttpError> {
+            let signature = format!("{} {}", req.method, body.len());
+            req.headers
+                .insert("x-signature", signature.try_into().unwrap());
+            Ok(())
+        },
+    );
+    let signature = signing_client
+        .post(format!("{url}signature"))
+        .json(&[1, 2])
+        .send()
+        .unwrap()
+        .bytes_sync()
+        .unwrap();
+    assert_eq!(signature, b"POST 5");
+    let mut pre = signing_client
+        .post(format!("{url}signature"))
+        .build()
+        .unwrap();
+    let body = client
+        .0
+        .exec
+        .generic_request_body_mut(&mut pre.request.body);
+    *body.unwrap() = crate::RequestBody::Read(Box::new(std::io::empty()));
+    let err = signing_client.send_pre(pre).err().unwrap();
+    assert!(err.is_invalid_request());
+
+    #[cfg(feature = "hmac")]
+    {
+        let signer = crate::signing::HmacSigner::new(b"secret");
+        let signature = client
+            .clone()
+            .with_signer(signer)
+            .post(format!("{url}signature?a=1"))
+            .json(&[1, 2])
+            .send()
+            .unwrap()
+            .bytes_sync()
+            .unwrap();
+        assert_eq!(signature, b"RP35+Pc7MzTgPrbASTqnhyr1l8uUkQg2TtQD7JADBkY=");
+    }
+
+    let logging_client = client.clone().with_middleware(logger);
+    logging_client.post(&url).json(&[1, 2]).send().unwrap();
+    logging_client.get(&url).send().unwrap();
+    client.get(&url).send().unwrap();
+    assert_eq!(*body_lengths.lock().unwrap(), vec![Some(5), Some(0)]);
+
+    let request = client
+        .put(format!("{url}signed"))
+        .header("x-amz-date", "20150830T123600Z")
+        .json(&[1, 2])
+        .build_generic()
+        .unwrap();
+    assert_eq!(request.method, Method::PUT);
+    assert_eq!(request.headers["x-amz-date"], "20150830T123600Z");
+    assert!(matches!(request.body, crate::RequestBody::Bytes(b) if b == b"[1,2]"));
+
+    let pre = client
+        .get(&url)
+        .timeout(std::time::Duration::from_secs(30))
+        .connect_timeout(std::time::Duration::from_secs(5))
+        .build()
+        .unwrap();
+    assert_eq!(pre.timeout, Some(std::time::Duration::from_secs(30)));
+    assert_eq!(pre.connect_timeout, Some(std::time::Duration::from_secs(5)));
+    client.send_pre(pre).unwrap();
+
+    // Panics in taps and middleware become errors.
+    let mut pre = client.get(&url).build().unwrap();
+    pre.tap = Some(Arc::new(|_res: &mut Response<()>| panic!("tap failure")));
+    let err = client.send_pre(pre).err().unwrap();
+    assert_eq!(err.to_string(), "tap panicked: tap failure");
+    let panicking_client = client
+        .clone()
+        .with_middleware(|_req: &mut crate::middleware::MiddlewareRequest<'_>| panic!());
+    assert!(panicking_client.get(&url).send().is_err());
+
+    // A burst of failures exhausts the retry budget.
+    let policy = crate::retry::RetryPolicy::new(3).with_budget(0.1);
+    let retry_client = crate::Client::new(crate::sync::RetryExecutor::new(
+        client.executor_cloned(),
+        policy,
+    ));
+    let attempts = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let mut counts = Vec::new();
+    for _ in 0..5 {
+        let mut pre = retry_client
+            .get(format!("{url}status/503"))
+            .build()
+            .unwrap();
+        let attempts2 = attempts.clone();
+        pre.tap = Some(Arc::new(move |_res: &mut Response<()>| {
+            attempts2.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        }));
+        let res = retry_client.send_pre(pre).unwrap();
+        assert_eq!(res.status, StatusCode::SERVICE_UNAVAILABLE);
+        counts.push(attempts.swap(0, std::sync::atomic::Ordering::SeqCst));
+    }
+    assert_eq!(counts, vec![4, 4, 4, 2, 1]);
+
+    // Non-idempotent requests are only retried when allowed.
+    for (allow, expected) in [(false, 1), (true, 2)] {
+        let policy = crate::retry::RetryPolicy::new(1).retry_non_idempotent(allow);
+        let retry_client = crate::Client::new(crate::sync::RetryExecutor::new(
+            client.executor_cloned(),
+            policy,
+        ));
+        let mut pre = retry_client
+            .post(format!("{url}status/503"))
+            .build()
+            .unwrap();
+        let attempts2 = attempts.clone();
+        pre.tap = Some(Arc::new(move |_res: &mut Response<()>| {
+            attempts2.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        }));
+        retry_client.send_pre(pre).unwrap();
+        assert_eq!(
+            attempts.swap(0, std::sync::atomic::Ordering::SeqCst),
+            expected
+        );
+    }
+
+    let reset_server = start_reset_server();
+    let policy = crate::retry::RetryPolicy::new(1).retry_on_reset(true);
+    let retry_client = crate::Client::new(crate::sync::RetryExecutor::new(
+        client.executor_cloned(),
+        policy,
+    ));
+    let body = retry_client
+        .get(format!("http://{RESET_URL}/"))
+        .send()
+        .unwrap()
+        .bytes_sync()
+        .unwrap();
+    assert_eq!(body, b"ok");
+    reset_server.join().unwrap();
+
+    #[cfg(feature = "urlencoding")]
+    {
+        let value = client
+            .post(format!("{url}form"))
+            .form_urlencoded_pairs([("name", "a b&c"), ("tag", "ü"), ("tag", "x=y")])
+            .send()
+            .unwrap()
+            .json_sync::<serde_json::Value>()
+            .unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "content_type": "application/x-www-form-urlencoded",
+                "body": "name=a+b%26c&tag=%C3%BC&tag=x%3Dy",
+            })
+        );
+    }
+
+    let value = client
+        .post(format!("{url}form"))
+        .body_async_read(futures::io::Cursor::new(b"streamed".to_vec()))
+        .send()
+        .unwrap()
+        .json_sync::<serde_json::Value>()
+        .unwrap();
+    assert_eq!(value["body"], "streamed");
+
+    // The header is sent and the interim `100 Continue` response is
+    // skipped.
+    let continue_server = start_continue_server();
+    let value = client
+        .post(format!("http://{CONTINUE_URL}/"))
+        .expect_continue()
+        .json_value(serde_json::json!("data"))
+        .send()
+        .unwrap()
+        .error_for_status()
+        .unwrap()
+        .json_sync::<serde_json::Value>()
+        .unwrap();
+    continue_server.join().unwrap();
+    assert_eq!(value["body"], "\"data\"");
+    assert_eq!(value["expect"], "100-continue");
+
+    // A content type set by the caller is kept.
+    let value = client
+        .post(format!("{url}form"))
+        .header(http::header::CONTENT_TYPE, "application/vnd.api+json")
+        .json_value(serde_json::json!({"a": 1}))
+        .send()
+        .unwrap()
+        .json_sync::<serde_json::Value>()
+        .unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!({
+            "content_type": "application/vnd.api+json",
+            "body": "{\"a\":1}",
+        })
+    );
+
+    #[cfg(feature = "compression")]
+    {
+        let value = client
+            .post(format!("{url}gunzip"))
+            .json(&"compressed")
+            .gzip_body()
+            .send()
+            .unwrap()
+            .json_sync::<serde_json::Value>()
+            .unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({"content_encoding": "gzip", "body": "\"compressed\""})
+        );
+    }
+
+    #[cfg(feature = "compression")]
+    for encoding in ["gzip", "deflate", "br"] {
+        let res = client
+            .get(format!("{url}{encoding}"))
+            .no_decompress()
+            .send()
+            .unwrap();
+        assert_eq!(
+            res.header_str(http::header::CONTENT_ENCODING),
+            Some(encoding)
+        );
+        let bytes = res.bytes_decoded_sync().unwrap();
+        let value = serde_json::from_slice::<serde_json::Value>(&bytes).unwrap();
+        assert_eq!(value["url"], format!("/{encoding}"));
+    }
+
+    let res = client.get(format!("{url}chunked")).send().unwrap();
+    assert_eq!(
+        res.bytes_sync().unwrap(),
+        CHUNKED_BODY.repeat(1000).as_bytes()
+    );
+    // Chunks are yielded as they are read, not after buffering the body.
+    let res = client.get(format!("{url}chunked")).send().unwrap();
+    let chunks = res.chunks_sync().collect::<Result<Vec<_>, _>>().unwrap();
+    assert!(chunks.len() > 1);
+    assert_eq!(chunks.concat(), CHUNKED_BODY.repeat(1000).as_bytes());
+    #[cfg(feature = "compression")]
+    {
+        let res = client.get(format!("{url}gzip-chunked")).send().unwrap();
+        assert!(!res.headers.contains_key(http::header::CONTENT_ENCODING));
+        assert_eq!(
+            res.bytes_sync().unwrap(),
+            CHUNKED_BODY.repeat(1000).as_bytes()
+        );
+    }
+    // `Transfer-Encoding` is left to the transport.
+    let mut headers = http::HeaderMap::new();
+    headers.insert(
+        http::header::TRANSFER_ENCODING,
+        http::HeaderValue::from_static("gzip, chunked"),
+    );
+    assert_eq!(
+        crate::compression::Encoding::take_from_headers(&mut headers),
+        None
+    );
+    assert!(headers.contains_key(http::header::TRANSFER_ENCODING));
+
+    #[cfg(feature = "compression")]
+    for encoding in ["gzip", "deflate", "br"] {
+        let res = client.get(format!("{url}{encoding}")).send().unwrap();
+        assert!(!res.headers.contains_key(http::header::CONTENT_ENCODING));
+        let value = res.json_sync::<serde_json::Value>().unwrap();
+        assert_eq!(value["url"], format!("/{encoding}"));
+        assert_eq!(
+            value["accept_encoding"],
+            crate::compression::ACCEPT_ENCODING
+        );
+    }
+
+    // Mutating requests without a body are sent with `Content-Length: 0`.
+    for method in [Method::POST, Method::PUT, Method::PATCH] {
+        let value = client
+            .request(method.clone(), format!("{url}method"))
+            .send()
+            .unwrap()
+            .json_sync::<serde_json::Value>()
+            .unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({"method": method.as_str(), "content_length": "0"})
+        );
+    }
+    for (builder, method) in [
+        (client.options(format!("{url}method")), "OPTIONS"),
+        (client.trace(format!("{url}method")), "TRACE"),
+    ] {
+        let value = builder
+            .send()
+            .unwrap()
+            .json_sync::<serde_json::Value>()
+            .unwrap();
+        assert_eq!(value["method"], method);
+    }
+
+    assert!(client
+        .get(format!("{url}method"))
+        .json_value(serde_json::json!(1))
+        .build()
+        .is_ok());
+    let strict = client.with_overrides().strict_mode(true).build();
+    let err = strict
+        .head(format!("{url}method"))
+        .json_value(serde_json::json!(1))
+        .build()
+        .err()
+        .unwrap();
+    assert!(err.is_invalid_request());
+    assert!(err
+        .to_string()
+        .contains("HEAD requests must not have a body"));
+    assert!(strict.get(format!("{url}method")).send().is_ok());
+    assert!(strict
+        .post(format!("{url}method"))
+        .json_value(serde_json::json!(1))
+        .send()
+        .is_ok());
+
+    let limited = client
+        .with_overrides()
+        .max_request_body_size(Some(4))
+        .build();
+    let value = limited
+        .post(format!("{url}form"))
+        .json_value(serde_json::json!("ok"))
+        .send()
+        .unwrap()
+        .json_sync::<serde_json::Value>()
+        .unwrap();
+    assert_eq!(value["body"], "\"ok\"");
+    let err = limited
+        .post(format!("{url}form"))
+        .json_value(serde_json::json!("too long"))
+        .send()
+        .err()
+        .unwrap();
+    assert!(err.is_request_too_large());
+    assert!(err.to_string().contains("limit of 4 bytes"));
+    let res = limited
+        .post(format!("{url}form"))
+        .body_async_read(futures::io::Cursor::new(b"streamed".to_vec()))
+        .send();
+    assert!(res.is_err());
+
+    // Redirects to URIs that can't be represented keep the requested URI.
+    let res = client.get(format!("{url}redirect-long")).send().unwrap();
+    assert_eq!(res.status, StatusCode::OK);
+    assert_eq!(res.uri.unwrap().to_string(), format!("{url}redirect-long"));
+
+    // Sync executors don't support `connect_to`, which host overrides use.
+    let addr = TEST_URL.parse::<std::net::SocketAddr>().unwrap();
+    let res = client
+        .with_overrides()
+        .host_overrides(
+            [("service.invalid".to_string(), addr)]
+                .into_iter()
+                .collect(),
+        )
+        .build()
+        .get("http://service.invalid:8080/host")
+        .send();
+    assert!(res.is_err());
+
+    let ua_client = client
+        .with_overrides()
+        .user_agent(Some(HeaderValue::from_static(crate::DEFAULT_USER_AGENT)))
+        .build();
+    let user_agent = ua_client
+        .get(format!("{url}user-agent"))
+        .send()
+        .unwrap()
+        .bytes_sync()
+        .unwrap();
+    assert_eq!(user_agent, crate::DEFAULT_USER_AGENT.as_bytes());
+    let user_agent = ua_client
+        .get(format!("{url}user-agent"))
+        .header(http::header::USER_AGENT, "custom")
+        .send()
+        .unwrap()
+        .bytes_sync()
+        .unwrap();
+    assert_eq!(user_agent, b"custom");
+
+    #[cfg(feature = "base64")]
+    {
+        let value = client
+            .get(format!("http://user:p%40ss@{TEST_URL}/authorization"))
+            .send()
+            .unwrap()
+            .json_sync::<serde_json::Value>()
+            .unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({"authorization": "Basic dXNlcjpwQHNz", "host": TEST_URL})
+        );
+    }
+
+    server.stop();
+}
+
+/// Checks for the sync harness that not every executor can pass:
+/// sub-second timeouts and repeated `Set-Cookie` headers.
+pub fn test_sync_executor_strict<E>(exec: E)
+where
+    E: HttpExecutor<
+        ResponseBody = crate::sync::GenericResponseBody,
+        Output = Result<crate::Response<crate::sync::GenericResponseBody>, HttpError>,
+    >,
+    E: Clone,
+{
+    let server = start_test_server();
+    let client = crate::Client::new(exec);
+    let url = format!("http://{TEST_URL}/");
+
+    // The timeout also covers a body that stalls after the head. Buffering
+    // executors fail before returning the response.
+    let err = client
+        .get(format!("{url}slow-body"))
+        .timeout(std::time::Duration::from_millis(100))
+        .send()
+        .and_then(|res| res.bytes_sync())
+        .err()
+        .unwrap();
+    assert_timeout(&err);
+
+    let err = client
+        .get(format!("{url}slow"))
+        .timeout(std::time::Duration::from_millis(50))
+        .send()
+        .err()
+        .unwrap();
+    assert_timeout(&err);
+
+    let err = client
+        .get(format!("{url}slow"))
+        .deadline(std::time::Instant::now() + std::time::Duration::from_millis(50))
+        .send()
+        .err()
+        .unwrap();
+    assert_timeout(&err);
+    let err = client
+        .get(&url)
+        .deadline(std::time::Instant::now())
+        .send()
+        .err()
+        .unwrap();
+    assert_timeout(&err);
+
+    // Repeated response headers are kept.
+    let res = client.get(format!("{url}created")).send().unwrap();
+    assert_eq!(
+        res.header_all_joined("Vary").as_deref(),
+        Some("accept, accept-encoding")
+    );
+
+    // A derived client has its own timeout, but shares the cookie jar.
+    #[cfg(feature = "cookies")]
+    {
+        let client = crate::Client::new_with_cookie_jar(client.executor_cloned());
+        let derived = client
+            .with_overrides()
+            .timeout(std::time::Duration::from_millis(50))
+            .build();
+        let err = derived.get(format!("{url}slow")).send().err().unwrap();
+        assert_timeout(&err);
+        client.get(format!("{url}slow")).send().unwrap();
+
+        let jar_url = url.parse::<url::Url>().unwrap();
+        client
+            .0
+            .cookies
+            .as_ref()
+            .unwrap()
+            .store_response_cookies(vec![cookie::Cookie::parse("shared=1").unwrap()], &jar_url);
+        let cookies = derived
+            .get(format!("{url}cookies"))
+            .send()
+            .unwrap()
+            .json_sync::<Vec<String>>()
+            .unwrap();
+        assert_eq!(cookies, vec!["shared=1"]);
+
+        let sent_cookies = || {
+            let header = client
+                .get(format!("{url}cookies"))
+                .send()
+                .unwrap()
+                .json_sync::<Vec<String>>()
+                .unwrap();
+            let mut cookies = header
+                .iter()
+                .flat_map(|h| h.split("; "))
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>();
+            cookies.sort();
+            cookies
+        };
+
+        // Cookies are stored from error responses too.
+        let res = client.get(format!("{url}login")).send().unwrap();
+        assert_eq!(res.status, StatusCode::UNAUTHORIZED);
+        assert_eq!(sent_cookies(), vec!["session=abc", "shared=1"]);
+
+        // Cookies set on the request are merged with the jar, and win on
+        // conflicts.
+        let cookies = client
+            .get(format!("{url}cookies"))
+            .cookie("session", "override")
+            .cookie("extra", "1")
+            .send()
+            .unwrap()
+            .json_sync::<Vec<String>>()
+            .unwrap();
+        assert_eq!(cookies, vec!["session=override; extra=1; shared=1"]);
+
+        // Expired, foreign-domain, malformed and other-path cookies are not
+        // sent.
+        client.get(format!("{url}set-cookies")).send().unwrap();
+        assert_eq!(
+            sent_cookies(),
+            vec!["a=1", "session=abc", "shared=1", "temp=1"]
+        );
+    }
 
-    server.unblock();
+    server.stop();
 }
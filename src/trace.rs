@@ -0,0 +1,136 @@
+//! Request spans for the `tracing` feature.
+//!
+//! The client creates a span for every request and hands it to the
+//! executor with [`crate::HttpExecutor::instrument_output`], since only the
+//! executor knows its output: async executors run their future in the span,
+//! sync executors record the result. The status is recorded by a tap when
+//! the response arrives.
+
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use tracing::Instrument;
+
+use crate::{HttpError, RequestPre, Response, Uri};
+
+struct SpanState {
+    span: tracing::Span,
+    start: Instant,
+    responded: AtomicBool,
+    finished: AtomicBool,
+}
+
+impl SpanState {
+    fn elapsed_ms(&self) -> u64 {
+        self.start.elapsed().as_millis() as u64
+    }
+
+    fn on_response(&self, res: &Response<()>) {
+        self.responded.store(true, Ordering::Relaxed);
+        let elapsed_ms = self.elapsed_ms();
+        self.span.record("status", res.status.as_u16());
+        self.span.record("elapsed_ms", elapsed_ms);
+        tracing::debug!(parent: &self.span, status = res.status.as_u16(), elapsed_ms, "response received");
+    }
+}
+
+impl Drop for SpanState {
+    fn drop(&mut self) {
+        // Executors that don't report the outcome only drop the request.
+        if !self.finished.load(Ordering::Relaxed) && !self.responded.load(Ordering::Relaxed) {
+            let elapsed_ms = self.elapsed_ms();
+            self.span.record("elapsed_ms", elapsed_ms);
+            tracing::warn!(parent: &self.span, elapsed_ms, "request failed");
+        }
+    }
+}
+
+/// The span of a request, see [`crate::HttpExecutor::instrument_output`].
+pub struct RequestSpan(Arc<SpanState>);
+
+impl RequestSpan {
+    pub fn span(&self) -> &tracing::Span {
+        &self.0.span
+    }
+
+    /// Record the outcome of the request.
+    ///
+    /// Errors are recorded in the `error` field and logged with the error.
+    pub fn finish<T>(self, result: &Result<T, HttpError>) {
+        let state = &self.0;
+        state.finished.store(true, Ordering::Relaxed);
+        let elapsed_ms = state.elapsed_ms();
+        state.span.record("elapsed_ms", elapsed_ms);
+        if let Err(err) = result {
+            state.span.record("error", tracing::field::display(err));
+            tracing::warn!(parent: &state.span, error = %err, elapsed_ms, "request failed");
+        }
+    }
+
+    /// Run a future in the span, and record its outcome when it resolves.
+    pub fn instrument<F, T>(self, fut: F) -> impl Future<Output = Result<T, HttpError>>
+    where
+        F: Future<Output = Result<T, HttpError>>,
+    {
+        let span = self.0.span.clone();
+        async move {
+            let result = fut.instrument(span).await;
+            self.finish(&result);
+            result
+        }
+    }
+}
+
+/// The URI without credentials and query, which may contain secrets.
+fn redacted_uri(uri: &Uri) -> String {
+    let mut out = String::new();
+    if let Some(scheme) = uri.scheme_str() {
+        out.push_str(scheme);
+        out.push_str("://");
+    }
+    if let Some(host) = uri.host() {
+        out.push_str(host);
+    }
+    if let Some(port) = uri.port_u16() {
+        out.push_str(&format!(":{port}"));
+    }
+    out.push_str(uri.path());
+    out
+}
+
+/// Log a request that failed before it was sent.
+pub(crate) fn invalid_request<B>(pre: &RequestPre<B>, err: &HttpError) {
+    tracing::warn!(
+        method = %pre.request.method,
+        uri = %redacted_uri(&pre.request.uri),
+        error = %err,
+        "invalid request",
+    );
+}
+
+/// Create the span for a request and chain a tap that records the response.
+pub(crate) fn request_span<B>(pre: &mut RequestPre<B>) -> RequestSpan {
+    let span = tracing::info_span!(
+        "http_request",
+        method = %pre.request.method,
+        uri = %redacted_uri(&pre.request.uri),
+        status = tracing::field::Empty,
+        elapsed_ms = tracing::field::Empty,
+        error = tracing::field::Empty,
+    );
+    let state = Arc::new(SpanState {
+        span,
+        start: Instant::now(),
+        responded: AtomicBool::new(false),
+        finished: AtomicBool::new(false),
+    });
+    let tap_state = state.clone();
+    crate::chain_tap(pre, move |res| tap_state.on_response(res));
+    RequestSpan(state)
+}
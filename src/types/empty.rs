@@ -0,0 +1,79 @@
+//! Response bodies without content.
+//!
+//! [`crate::Respond`] fixes the output types per implementation, and the
+//! sync and async extension methods expect different ones, so there is one
+//! type per flavor.
+
+use crate::{HttpError, Respond};
+
+/// An empty response body for sync executors.
+#[cfg(feature = "sync")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EmptyResponseBody;
+
+#[cfg(feature = "sync")]
+impl Respond for EmptyResponseBody {
+    type Chunks = std::iter::Empty<Result<Vec<u8>, HttpError>>;
+    type BytesOutput = Result<Vec<u8>, HttpError>;
+    type Reader = std::io::Empty;
+
+    fn into_chunks(self) -> Self::Chunks {
+        std::iter::empty()
+    }
+
+    fn into_chunks_boxed(self: Box<Self>) -> Self::Chunks {
+        std::iter::empty()
+    }
+
+    fn bytes(self) -> Self::BytesOutput {
+        Ok(Vec::new())
+    }
+
+    fn bytes_boxed(self: Box<Self>) -> Self::BytesOutput {
+        Ok(Vec::new())
+    }
+
+    fn reader(self) -> Self::Reader {
+        std::io::empty()
+    }
+
+    fn reader_boxed(self: Box<Self>) -> Self::Reader {
+        std::io::empty()
+    }
+}
+
+/// An empty response body for async executors.
+#[cfg(feature = "async")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AsyncEmptyResponseBody;
+
+#[cfg(feature = "async")]
+impl Respond for AsyncEmptyResponseBody {
+    type Chunks = futures::stream::Empty<Result<Vec<u8>, HttpError>>;
+    type BytesOutput = std::future::Ready<Result<Vec<u8>, HttpError>>;
+    type Reader = futures::io::Empty;
+
+    fn into_chunks(self) -> Self::Chunks {
+        futures::stream::empty()
+    }
+
+    fn into_chunks_boxed(self: Box<Self>) -> Self::Chunks {
+        futures::stream::empty()
+    }
+
+    fn bytes(self) -> Self::BytesOutput {
+        std::future::ready(Ok(Vec::new()))
+    }
+
+    fn bytes_boxed(self: Box<Self>) -> Self::BytesOutput {
+        std::future::ready(Ok(Vec::new()))
+    }
+
+    fn reader(self) -> Self::Reader {
+        futures::io::empty()
+    }
+
+    fn reader_boxed(self: Box<Self>) -> Self::Reader {
+        futures::io::empty()
+    }
+}
@@ -1,14 +1,38 @@
+#[cfg(any(feature = "sync", feature = "async"))]
+mod empty;
+mod pool;
 mod request;
 mod response;
+mod stream;
+#[cfg(feature = "tls")]
+mod tls;
 
-use crate::Tapper;
+use crate::{cancel::CancelToken, Tapper};
 
-pub use self::{request::Request, response::Response};
+#[cfg(feature = "async")]
+pub use self::empty::AsyncEmptyResponseBody;
+#[cfg(feature = "sync")]
+pub use self::empty::EmptyResponseBody;
+#[cfg(feature = "tls")]
+pub use self::tls::{Certificate, Identity, TlsConfig};
+pub use self::{
+    pool::{HostPoolStats, PoolConfig, PoolStats},
+    request::{HeaderOrder, IntoUri, Request},
+    response::{ReasonPhrase, Response},
+    stream::BodyStream,
+};
+
+#[cfg(feature = "json")]
+pub(crate) use self::response::{json_body_error, strip_json_prefix};
 
 pub enum RequestBody {
     Empty,
     Bytes(Vec<u8>),
     Read(Box<dyn std::io::Read>),
+    /// An async stream, sent as it is read by async executors.
+    ///
+    /// Sync executors buffer the whole body before sending it.
+    Stream(BodyStream),
 }
 
 impl std::fmt::Debug for RequestBody {
@@ -17,7 +41,69 @@ impl std::fmt::Debug for RequestBody {
             Self::Empty => write!(f, "Empty"),
             Self::Bytes(arg0) => f.debug_tuple("Bytes").field(arg0).finish(),
             Self::Read(_) => f.debug_tuple("Read").field(&"...").finish(),
+            Self::Stream(_) => f.debug_tuple("Stream").field(&"...").finish(),
+        }
+    }
+}
+
+impl RequestBody {
+    /// Fail with a [`crate::HttpError::is_request_too_large`] error if the
+    /// body is larger than `limit` bytes.
+    ///
+    /// `Read` and `Stream` bodies are checked while they are sent.
+    pub(crate) fn limit(self, limit: u64) -> Result<Self, crate::HttpError> {
+        match self {
+            Self::Empty => Ok(Self::Empty),
+            Self::Bytes(bytes) => {
+                if bytes.len() as u64 > limit {
+                    return Err(crate::HttpError::new_request_too_large(limit));
+                }
+                Ok(Self::Bytes(bytes))
+            }
+            Self::Read(reader) => Ok(Self::Read(Box::new(LimitedReader {
+                reader,
+                limit,
+                read: 0,
+            }))),
+            #[cfg(feature = "async")]
+            Self::Stream(stream) => {
+                use futures::StreamExt;
+
+                let mut sent = 0u64;
+                let stream = stream.into_stream().map(move |chunk| {
+                    let chunk = chunk?;
+                    sent += chunk.len() as u64;
+                    if sent > limit {
+                        return Err(crate::HttpError::new_request_too_large(limit));
+                    }
+                    Ok(chunk)
+                });
+                Ok(Self::Stream(BodyStream::new(stream.boxed())))
+            }
+            #[cfg(not(feature = "async"))]
+            Self::Stream(stream) => match stream.never {},
+        }
+    }
+}
+
+/// Fails with an io error wrapping a [`crate::HttpError`] once more than
+/// `limit` bytes were read.
+struct LimitedReader {
+    reader: Box<dyn std::io::Read>,
+    limit: u64,
+    read: u64,
+}
+
+impl std::io::Read for LimitedReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let count = self.reader.read(buf)?;
+        self.read += count as u64;
+        if self.read > self.limit {
+            return Err(std::io::Error::other(
+                crate::HttpError::new_request_too_large(self.limit),
+            ));
         }
+        Ok(count)
     }
 }
 
@@ -45,18 +131,125 @@ impl From<String> for RequestBody {
     }
 }
 
+#[cfg(feature = "json")]
+impl TryFrom<serde_json::Value> for RequestBody {
+    type Error = crate::HttpError;
+
+    /// Serialize the value to JSON bytes.
+    fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
+        serde_json::to_vec(&value).map(Self::Bytes).map_err(|err| {
+            crate::HttpError::new(
+                crate::error::Kind::InvalidRequestJson,
+                Some(Box::new(err)),
+                None,
+            )
+        })
+    }
+}
+
+/// Where to open the connection for a request, instead of the host in the
+/// request URI.
+///
+/// The URI and the `Host` header are sent unchanged.
+///
+/// Supported by the hyper executor. Other executors fail requests that
+/// set it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConnectTo {
+    Addr(std::net::SocketAddr),
+    /// Path of a unix domain socket.
+    Unix(std::path::PathBuf),
+}
+
+impl From<std::net::SocketAddr> for ConnectTo {
+    fn from(addr: std::net::SocketAddr) -> Self {
+        Self::Addr(addr)
+    }
+}
+
 pub struct RequestPre<B> {
     pub request: Request<B>,
     pub timeout: Option<std::time::Duration>,
+    /// Absolute point in time by which the request must be done.
+    ///
+    /// If `timeout` is also set, the earlier of the two applies.
+    /// See [`RequestPre::remaining_timeout`].
+    pub deadline: Option<std::time::Instant>,
+    /// Timeout for establishing a connection.
+    ///
+    /// Separate from `timeout`, which covers the whole request.
+    /// Executors that can only configure this per client ignore it.
+    pub connect_timeout: Option<std::time::Duration>,
+    /// Connect to this target instead of the host in the URI.
+    pub connect_to: Option<ConnectTo>,
+    /// Abort the request when the token is cancelled.
+    ///
+    /// Executors that don't support cancellation only check the token
+    /// before sending the request.
+    pub cancel: Option<CancelToken>,
     pub tap: Option<Tapper>,
+    /// Decode the response body according to `Content-Encoding`.
+    ///
+    /// Only has an effect with the `compression` feature.
+    pub decompress: bool,
+    /// Fail if the request body is larger, in bytes.
+    ///
+    /// Set from `Client::with_max_request_body_size`. The client enforces
+    /// it for bodies exposed as a generic [`RequestBody`], executors with
+    /// a custom body type check it themselves.
+    pub max_request_body_size: Option<u64>,
+}
+
+impl<B> From<Request<B>> for RequestPre<B> {
+    fn from(request: Request<B>) -> Self {
+        Self::new(request)
+    }
 }
 
 impl<B> RequestPre<B> {
+    pub fn new(request: Request<B>) -> Self {
+        Self {
+            request,
+            timeout: None,
+            deadline: None,
+            connect_timeout: None,
+            connect_to: None,
+            cancel: None,
+            tap: None,
+            decompress: true,
+            max_request_body_size: None,
+        }
+    }
+
+    /// The timeout to use when dispatching the request: the smaller of
+    /// `timeout` and the time left until `deadline`.
+    ///
+    /// Fails with a timeout error if the deadline has already passed.
+    pub fn remaining_timeout(&self) -> Result<Option<std::time::Duration>, crate::HttpError> {
+        let Some(deadline) = self.deadline else {
+            return Ok(self.timeout);
+        };
+        let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+        if remaining.is_zero() {
+            return Err(crate::HttpError::new_timeout(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                "deadline exceeded",
+            )));
+        }
+        Ok(Some(self.timeout.map_or(remaining, |t| t.min(remaining))))
+    }
+
     pub fn map_body<B2, F: FnOnce(B) -> B2>(self, f: F) -> RequestPre<B2> {
         RequestPre {
             request: self.request.map_body(f),
             timeout: self.timeout,
+            deadline: self.deadline,
+            connect_timeout: self.connect_timeout,
+            connect_to: self.connect_to,
+            cancel: self.cancel,
             tap: self.tap,
+            decompress: self.decompress,
+            max_request_body_size: self.max_request_body_size,
         }
     }
 }
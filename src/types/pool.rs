@@ -0,0 +1,63 @@
+use std::{collections::HashMap, time::Duration};
+
+/// Connection pool statistics.
+///
+/// See [`crate::Client::pool_stats`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Statistics per host, keyed by `host:port` (or just the host if the
+    /// uri has no explicit port).
+    pub hosts: HashMap<String, HostPoolStats>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HostPoolStats {
+    /// Open connections that are not currently used by a request.
+    pub idle: usize,
+    /// Connections that are currently used by a request.
+    pub in_use: usize,
+}
+
+/// Connection pool settings, passed to executor constructors.
+///
+/// Unset values keep the backend default. Not every backend supports every
+/// setting, see the constructors of the executors:
+///
+/// | backend | idle per host | idle total | idle timeout |
+/// |---------|---------------|------------|--------------|
+/// | hyper   | unlimited     | unlimited  | 90s          |
+/// | ureq    | 1             | 100        | unsupported  |
+/// | surf    | 50 (all connections) | unsupported | unsupported |
+///
+/// minreq doesn't pool connections.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PoolConfig {
+    /// Maximum number of idle connections kept per host.
+    pub max_idle_per_host: Option<usize>,
+    /// Maximum number of idle connections kept in total.
+    pub max_idle: Option<usize>,
+    /// How long an idle connection is kept open.
+    pub idle_timeout: Option<Duration>,
+}
+
+impl PoolConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn max_idle_per_host(mut self, max: usize) -> Self {
+        self.max_idle_per_host = Some(max);
+        self
+    }
+
+    pub fn max_idle(mut self, max: usize) -> Self {
+        self.max_idle = Some(max);
+        self
+    }
+
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{header::HeaderMap, Extensions, Method, Uri, Version};
+use crate::{
+    header::{HeaderMap, HeaderName},
+    Extensions, Method, Uri, Version,
+};
 
 #[derive(Debug)]
 pub struct Request<B> {
@@ -65,3 +68,91 @@ impl<B> Request<B> {
         }
     }
 }
+
+/// Request extension that controls the order in which headers are written.
+///
+/// See [`crate::RequestBuilder::header_order`].
+#[derive(Clone, Debug)]
+pub struct HeaderOrder(pub Vec<HeaderName>);
+
+impl HeaderOrder {
+    /// Re-insert the headers so the listed names come first, in order.
+    /// All other headers keep their relative order.
+    pub(crate) fn apply(&self, headers: &mut HeaderMap) {
+        let mut pairs = Vec::with_capacity(headers.len());
+        let mut current = None;
+        for (name, value) in headers.drain() {
+            if let Some(name) = name {
+                current = Some(name);
+            }
+            // The first item always has a name.
+            pairs.push((current.clone().unwrap(), value));
+        }
+
+        for (i, name) in self.0.iter().enumerate() {
+            // Names listed twice are only emitted at their first position.
+            if self.0[..i].contains(name) {
+                continue;
+            }
+            for (_, value) in pairs.iter().filter(|(n, _)| n == name) {
+                headers.append(name.clone(), value.clone());
+            }
+        }
+        for (name, value) in pairs {
+            if !self.0.contains(&name) {
+                headers.append(name, value);
+            }
+        }
+    }
+}
+
+/// Values that can be converted into a request [`Uri`].
+///
+/// Implemented for the types `Uri` can be converted from with `TryFrom`, and
+/// for [`url::Url`] with the `url` feature. A separate trait is needed since
+/// `Uri` can't implement `TryFrom<Url>`.
+pub trait IntoUri {
+    fn into_uri(self) -> Result<Uri, http::Error>;
+}
+
+macro_rules! impl_into_uri {
+    ($($ty:ty),*) => {
+        $(
+            impl IntoUri for $ty {
+                fn into_uri(self) -> Result<Uri, http::Error> {
+                    Uri::try_from(self).map_err(Into::into)
+                }
+            }
+        )*
+    };
+}
+
+impl_into_uri!(
+    Uri,
+    &Uri,
+    &str,
+    String,
+    &String,
+    &[u8],
+    Vec<u8>,
+    http::uri::Parts
+);
+
+/// The fragment is not sent and is dropped.
+#[cfg(feature = "url")]
+impl IntoUri for &url::Url {
+    fn into_uri(self) -> Result<Uri, http::Error> {
+        let uri = match self.as_str().split_once('#') {
+            Some((uri, _fragment)) => uri,
+            None => self.as_str(),
+        };
+        uri.into_uri()
+    }
+}
+
+#[cfg(feature = "url")]
+impl IntoUri for url::Url {
+    fn into_uri(self) -> Result<Uri, http::Error> {
+        (&self).into_uri()
+    }
+}
@@ -21,6 +21,12 @@ pub struct Response<B> {
     pub body: B,
 }
 
+/// Response extension with the reason phrase sent by the server.
+///
+/// See [`Response::reason`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReasonPhrase(pub String);
+
 impl<B: Default> Default for Response<B> {
     fn default() -> Self {
         Self {
@@ -38,8 +44,8 @@ impl<B: Clone> Clone for Response<B> {
     fn clone(&self) -> Self {
         Self {
             uri: self.uri.clone(),
-            status: self.status.clone(),
-            version: self.version.clone(),
+            status: self.status,
+            version: self.version,
             headers: self.headers.clone(),
             extensions: Default::default(),
             body: self.body.clone(),
@@ -132,6 +138,41 @@ impl<B> Response<B> {
     }
 
     pub fn error_for_status(self) -> Result<Self, HttpError> {
+        self.error_for_status_ref()?;
+        Ok(self)
+    }
+
+    /// Like [`Self::error_for_status`], with a custom predicate for which
+    /// statuses are acceptable, for example to accept `304 Not Modified`.
+    pub fn error_for_status_if<F>(self, is_ok: F) -> Result<Self, HttpError>
+    where
+        F: FnOnce(http::StatusCode) -> bool,
+    {
+        if is_ok(self.status) {
+            Ok(self)
+        } else {
+            Err(HttpError::new(
+                crate::error::Kind::NonSuccessStatus(self.status),
+                None,
+                None,
+            ))
+        }
+    }
+
+    /// Build the error for a non-success status, with part of the body.
+    #[cfg(any(feature = "sync", feature = "async"))]
+    pub(crate) fn status_error_with_body(&self, body: &[u8]) -> HttpError {
+        let message = Some(String::from_utf8_lossy(body).into_owned()).filter(|m| !m.is_empty());
+        HttpError::new(
+            crate::error::Kind::NonSuccessStatus(self.status),
+            None,
+            message,
+        )
+    }
+
+    /// Like [`Self::error_for_status`], but doesn't consume the response,
+    /// so the body can still be read on failure.
+    pub fn error_for_status_ref(&self) -> Result<(), HttpError> {
         if !self.status.is_success() {
             Err(HttpError::new(
                 crate::error::Kind::NonSuccessStatus(self.status),
@@ -139,7 +180,198 @@ impl<B> Response<B> {
                 None,
             ))
         } else {
-            Ok(self)
+            Ok(())
+        }
+    }
+
+    /// The `Content-Length` header.
+    ///
+    /// When the body is decompressed, the header is removed, since it
+    /// doesn't match the decoded body.
+    pub fn content_length(&self) -> Option<u64> {
+        self.header_str(http::header::CONTENT_LENGTH)?.parse().ok()
+    }
+
+    /// The time until the response headers were received.
+    ///
+    /// Only recorded if the client has an observer, see
+    /// [`crate::Client::with_observer`].
+    pub fn elapsed(&self) -> Option<std::time::Duration> {
+        self.extensions
+            .get::<crate::metrics::Elapsed>()
+            .map(|elapsed| elapsed.0)
+    }
+
+    /// The reason phrase of the status line, like `OK` in `200 OK`.
+    ///
+    /// The hyper, ureq and minreq executors keep the phrase sent by the
+    /// server. Otherwise this is the canonical reason of the status code.
+    pub fn reason(&self) -> Option<&str> {
+        match self.extensions.get::<ReasonPhrase>() {
+            Some(reason) => Some(&reason.0),
+            None => self.status.canonical_reason(),
+        }
+    }
+
+    /// Get a header value as a string.
+    ///
+    /// Returns `None` if the header is missing or not valid UTF-8.
+    /// For headers with multiple values, the first one is returned.
+    pub fn header_str(&self, name: impl http::header::AsHeaderName) -> Option<&str> {
+        self.headers.get(name)?.to_str().ok()
+    }
+
+    /// All values of a header, joined with `, `.
+    ///
+    /// For headers that can appear multiple times, like `Vary` or `Via`.
+    /// Values that are not valid UTF-8 are skipped. Returns `None` if there
+    /// is no valid value. Don't use this for `Set-Cookie`, which can't be
+    /// combined.
+    pub fn header_all_joined(&self, name: impl http::header::AsHeaderName) -> Option<String> {
+        let values = self
+            .headers
+            .get_all(name)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .collect::<Vec<_>>();
+        if values.is_empty() {
+            return None;
         }
+        Some(values.join(", "))
     }
+
+    /// The parsed `Content-Type` header.
+    ///
+    /// Returns `None` if the header is missing or malformed.
+    #[cfg(feature = "mime")]
+    pub fn content_type(&self) -> Option<mime::Mime> {
+        self.header_str(http::header::CONTENT_TYPE)?.parse().ok()
+    }
+
+    /// Fail unless the `Content-Type` is a JSON media type, like
+    /// `application/json` or `application/problem+json`.
+    #[cfg(feature = "json")]
+    pub(crate) fn expect_json_content_type(&self) -> Result<(), crate::HttpError> {
+        #[cfg(feature = "mime")]
+        let is_json = self.content_type().is_some_and(|mime| {
+            (mime.type_() == mime::APPLICATION && mime.subtype() == mime::JSON)
+                || mime.suffix() == Some(mime::JSON)
+        });
+        #[cfg(not(feature = "mime"))]
+        let is_json = {
+            let essence = self
+                .header_str(http::header::CONTENT_TYPE)
+                .and_then(|value| value.split(';').next())
+                .map(|essence| essence.trim().to_ascii_lowercase())
+                .unwrap_or_default();
+            essence == "application/json" || essence.ends_with("+json")
+        };
+        if is_json {
+            return Ok(());
+        }
+        let content_type = self.header_str(http::header::CONTENT_TYPE);
+        let message = match content_type {
+            Some(content_type) => format!("expected JSON, got '{content_type}'"),
+            None => "expected JSON, got no content type".to_string(),
+        };
+        Err(crate::HttpError::new(
+            crate::error::Kind::UnexpectedContentType,
+            None,
+            Some(message),
+        ))
+    }
+
+    /// The parsed `Location` header.
+    ///
+    /// The value is not resolved against the request URI, so it may be
+    /// relative (like `/login`).
+    pub fn location(&self) -> Option<http::Uri> {
+        self.header_str(http::header::LOCATION)?.parse().ok()
+    }
+
+    /// Abandon the response without reading the rest of the body.
+    ///
+    /// Same as dropping the response or a partially read body: the
+    /// connection is closed instead of being returned to the pool with
+    /// unread data.
+    pub fn abort(self) {
+        drop(self);
+    }
+}
+
+/// A response with a buffered body, see `Response::buffered_sync` and
+/// `Response::buffered_async`.
+///
+/// The body can be inspected any number of times, for example parsed and
+/// then logged.
+impl Response<Vec<u8>> {
+    /// Deserialize the body as JSON.
+    ///
+    /// The value can borrow from the body, like `&str` fields.
+    #[cfg(feature = "json")]
+    pub fn json<'a, T: serde::Deserialize<'a>>(&'a self) -> Result<T, HttpError> {
+        serde_json::from_slice(&self.body).map_err(|err| json_body_error(err, &self.body))
+    }
+}
+
+/// Wrap a JSON deserialization error for `body`.
+///
+/// The message shows the part of the body around the error location.
+#[cfg(feature = "json")]
+pub(crate) fn json_body_error(err: serde_json::Error, body: &[u8]) -> HttpError {
+    let message = json_error_snippet(&err, body).map(|snippet| format!("near `{snippet}`"));
+    HttpError::new(
+        crate::error::Kind::InvalidResponseJson,
+        Some(Box::new(err)),
+        message,
+    )
+}
+
+/// About 80 bytes of the body around the location of a JSON error.
+#[cfg(feature = "json")]
+fn json_error_snippet(err: &serde_json::Error, body: &[u8]) -> Option<String> {
+    const CONTEXT: usize = 40;
+
+    // serde_json reports line 0 for errors without a location.
+    if err.line() == 0 || body.is_empty() {
+        return None;
+    }
+    let line_start = if err.line() == 1 {
+        0
+    } else {
+        body.iter()
+            .enumerate()
+            .filter(|(_, b)| **b == b'\n')
+            .nth(err.line() - 2)
+            .map(|(pos, _)| pos + 1)?
+    };
+    let offset = (line_start + err.column().saturating_sub(1)).min(body.len() - 1);
+
+    // Don't cut UTF-8 sequences in half.
+    let is_continuation = |pos: usize| body.get(pos).is_some_and(|b| b & 0xC0 == 0x80);
+    let mut start = offset.saturating_sub(CONTEXT);
+    while is_continuation(start) {
+        start += 1;
+    }
+    let mut end = (offset + CONTEXT).min(body.len());
+    while is_continuation(end) {
+        end += 1;
+    }
+
+    let snippet = String::from_utf8_lossy(&body[start..end]).replace(['\n', '\r', '\t'], " ");
+    let prefix = if start > 0 { "..." } else { "" };
+    let suffix = if end < body.len() { "..." } else { "" };
+    Some(format!("{prefix}{snippet}{suffix}"))
+}
+
+/// Strip a required anti-hijacking prefix (like `)]}',\n`) from a JSON body.
+#[cfg(feature = "json")]
+pub(crate) fn strip_json_prefix<'a>(bytes: &'a [u8], prefix: &str) -> Result<&'a [u8], HttpError> {
+    bytes.strip_prefix(prefix.as_bytes()).ok_or_else(|| {
+        HttpError::new(
+            crate::error::Kind::InvalidResponseJson,
+            None,
+            Some(format!("expected JSON prefix {prefix:?}")),
+        )
+    })
 }
@@ -0,0 +1,81 @@
+use crate::HttpError;
+
+/// A streaming request body, see [`crate::RequestBody::Stream`].
+///
+/// Can only be created with the `async` feature. The type always exists, so
+/// sync executors compile no matter which features are enabled, and can
+/// buffer the body with [`Self::read_to_end_blocking`].
+pub struct BodyStream {
+    #[cfg(feature = "async")]
+    pub(crate) stream: crate::DynChunksStream,
+    #[cfg(not(feature = "async"))]
+    pub(crate) never: std::convert::Infallible,
+}
+
+impl BodyStream {
+    #[cfg(feature = "async")]
+    pub fn new(stream: crate::DynChunksStream) -> Self {
+        Self { stream }
+    }
+
+    /// Stream the data of an async reader, for example a file.
+    #[cfg(feature = "async")]
+    pub fn from_async_read<R>(reader: R) -> Self
+    where
+        R: futures::io::AsyncRead + Send + 'static,
+    {
+        use futures::{AsyncReadExt, StreamExt};
+
+        let stream = futures::stream::try_unfold(Box::pin(reader), |mut reader| async move {
+            let mut buffer = vec![0; 8 * 1024];
+            let count = reader.read(&mut buffer).await.map_err(|err| {
+                HttpError::new_io(err, Some("could not read request body".to_string()))
+            })?;
+            if count == 0 {
+                return Ok(None);
+            }
+            buffer.truncate(count);
+            Ok(Some((buffer, reader)))
+        })
+        .boxed();
+        Self::new(stream)
+    }
+
+    /// Stream the chunks pushed through the returned sender, for example
+    /// from another task while the request is in flight.
+    ///
+    /// `buffer` is the capacity of the channel, see
+    /// [`futures::channel::mpsc::channel`]. The body ends when the sender
+    /// and all its clones are dropped.
+    #[cfg(feature = "async")]
+    pub fn channel(buffer: usize) -> (futures::channel::mpsc::Sender<Vec<u8>>, Self) {
+        use futures::StreamExt;
+
+        let (sender, receiver) = futures::channel::mpsc::channel(buffer);
+        (sender, Self::new(receiver.map(Ok).boxed()))
+    }
+
+    #[cfg(feature = "async")]
+    pub fn into_stream(self) -> crate::DynChunksStream {
+        self.stream
+    }
+
+    /// Read the whole body, blocking the current thread.
+    ///
+    /// For sync executors, which can't stream async bodies. Streams that
+    /// depend on a specific async runtime may fail when polled outside of
+    /// it.
+    pub fn read_to_end_blocking(self) -> Result<Vec<u8>, HttpError> {
+        #[cfg(feature = "async")]
+        return futures::executor::block_on(futures::TryStreamExt::try_concat(self.stream));
+        #[cfg(not(feature = "async"))]
+        match self.never {}
+    }
+}
+
+#[cfg(feature = "async")]
+impl From<crate::DynChunksStream> for BodyStream {
+    fn from(stream: crate::DynChunksStream) -> Self {
+        Self::new(stream)
+    }
+}
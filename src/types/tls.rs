@@ -0,0 +1,222 @@
+use std::path::Path;
+
+use crate::HttpError;
+
+/// A DER-encoded X.509 certificate.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Certificate(Vec<u8>);
+
+impl Certificate {
+    pub fn from_der(der: Vec<u8>) -> Self {
+        Self(der)
+    }
+
+    /// Load all certificates from PEM data, for example a CA bundle.
+    ///
+    /// Fails if the data contains no certificate.
+    pub fn from_pem(pem: &[u8]) -> Result<Vec<Self>, HttpError> {
+        let certs = pem_blocks(pem, "CERTIFICATE")?
+            .into_iter()
+            .map(Self)
+            .collect::<Vec<_>>();
+        if certs.is_empty() {
+            return Err(HttpError::new_custom("no certificate found in PEM data"));
+        }
+        Ok(certs)
+    }
+
+    /// Load all certificates from a PEM file. See [`Self::from_pem`].
+    pub fn from_pem_file(path: impl AsRef<Path>) -> Result<Vec<Self>, HttpError> {
+        Self::from_pem(&read_file(path.as_ref())?)
+    }
+
+    pub fn der(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// The certificate in PEM format, for TLS stacks that don't accept DER.
+    pub fn to_pem(&self) -> String {
+        to_pem("CERTIFICATE", &self.0)
+    }
+}
+
+impl std::fmt::Debug for Certificate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Certificate")
+            .field("len", &self.0.len())
+            .finish()
+    }
+}
+
+/// A client certificate chain with its private key, for mutual TLS.
+#[derive(Clone)]
+pub struct Identity {
+    certs: Vec<Certificate>,
+    key: Vec<u8>,
+}
+
+impl Identity {
+    /// Load an identity from a PEM certificate chain and a PEM private key.
+    ///
+    /// The key must be in PKCS#8 format (`BEGIN PRIVATE KEY`), since that
+    /// is the only format every backend understands.
+    pub fn from_pem(cert_chain: &[u8], key: &[u8]) -> Result<Self, HttpError> {
+        let certs = Certificate::from_pem(cert_chain)?;
+        let key = pem_blocks(key, "PRIVATE KEY")?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                HttpError::new_custom("no PKCS#8 private key ('BEGIN PRIVATE KEY') found")
+            })?;
+        Ok(Self { certs, key })
+    }
+
+    /// Load an identity from PEM files. See [`Self::from_pem`].
+    pub fn from_pem_files(
+        cert_chain: impl AsRef<Path>,
+        key: impl AsRef<Path>,
+    ) -> Result<Self, HttpError> {
+        Self::from_pem(&read_file(cert_chain.as_ref())?, &read_file(key.as_ref())?)
+    }
+
+    /// The certificate chain, starting with the client certificate.
+    pub fn certs(&self) -> &[Certificate] {
+        &self.certs
+    }
+
+    /// The DER-encoded PKCS#8 private key.
+    pub fn key_der(&self) -> &[u8] {
+        &self.key
+    }
+
+    /// The private key in PEM format, for TLS stacks that don't accept DER.
+    pub fn key_pem(&self) -> String {
+        to_pem("PRIVATE KEY", &self.key)
+    }
+}
+
+impl std::fmt::Debug for Identity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The key is not printed.
+        f.debug_struct("Identity")
+            .field("certs", &self.certs)
+            .finish_non_exhaustive()
+    }
+}
+
+/// TLS settings, passed to executor constructors.
+///
+/// Each executor translates the settings to its TLS stack, see
+/// `UreqExecutor::with_tls_config` and `HyperExecutor::new_with_tls`.
+/// Other executors can't be configured.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct TlsConfig {
+    /// Additional trusted root certificates, on top of the default ones of
+    /// the backend.
+    pub root_certs: Vec<Certificate>,
+    /// Client certificate for mutual TLS.
+    pub identity: Option<Identity>,
+    /// Protocols to offer with ALPN, like `h2` or `http/1.1`.
+    ///
+    /// Empty means the backend default.
+    pub alpn_protocols: Vec<String>,
+    #[cfg(feature = "dangerous")]
+    danger_accept_invalid_certs: bool,
+}
+
+impl TlsConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_root_cert(mut self, cert: Certificate) -> Self {
+        self.root_certs.push(cert);
+        self
+    }
+
+    pub fn identity(mut self, identity: Identity) -> Self {
+        self.identity = Some(identity);
+        self
+    }
+
+    pub fn alpn_protocols<I, S>(mut self, protocols: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.alpn_protocols = protocols.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Accept any server certificate, including expired and self-signed
+    /// ones, and certificates for other hosts.
+    ///
+    /// **WARNING**: this disables all protection against man-in-the-middle
+    /// attacks. Only use it for testing.
+    #[cfg(feature = "dangerous")]
+    pub fn danger_accept_invalid_certs(mut self, accept: bool) -> Self {
+        self.danger_accept_invalid_certs = accept;
+        self
+    }
+
+    /// Whether certificate verification is disabled.
+    ///
+    /// Always `false` without the `dangerous` feature.
+    pub fn accepts_invalid_certs(&self) -> bool {
+        #[cfg(feature = "dangerous")]
+        return self.danger_accept_invalid_certs;
+        #[cfg(not(feature = "dangerous"))]
+        false
+    }
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>, HttpError> {
+    std::fs::read(path)
+        .map_err(|err| HttpError::new_io(err, Some(format!("could not read '{}'", path.display()))))
+}
+
+/// Decode all PEM blocks with the given label.
+fn pem_blocks(pem: &[u8], label: &str) -> Result<Vec<Vec<u8>>, HttpError> {
+    let pem = std::str::from_utf8(pem)
+        .map_err(|_| HttpError::new_custom("PEM data is not valid UTF-8"))?;
+    let begin = format!("-----BEGIN {label}-----");
+    let end = format!("-----END {label}-----");
+
+    let mut blocks = Vec::new();
+    let mut current: Option<String> = None;
+    for line in pem.lines().map(str::trim) {
+        if line == begin {
+            current = Some(String::new());
+        } else if line == end {
+            let data = current
+                .take()
+                .ok_or_else(|| HttpError::new_custom(format!("unexpected '{end}' in PEM data")))?;
+            let der = base64::decode(data).map_err(|err| {
+                HttpError::new_custom_with_cause("invalid base64 in PEM data", err)
+            })?;
+            blocks.push(der);
+        } else if let Some(data) = &mut current {
+            data.push_str(line);
+        }
+    }
+    if current.is_some() {
+        return Err(HttpError::new_custom(format!(
+            "missing '{end}' in PEM data"
+        )));
+    }
+    Ok(blocks)
+}
+
+fn to_pem(label: &str, der: &[u8]) -> String {
+    let encoded = base64::encode(der);
+    let mut pem = format!("-----BEGIN {label}-----\n");
+    // PEM lines are at most 64 characters. Base64 is ASCII, so the chunks
+    // are valid UTF-8.
+    for line in encoded.as_bytes().chunks(64) {
+        pem.push_str(std::str::from_utf8(line).unwrap());
+        pem.push('\n');
+    }
+    pem.push_str(&format!("-----END {label}-----\n"));
+    pem
+}
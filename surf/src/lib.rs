@@ -0,0 +1,356 @@
+use std::str::FromStr;
+
+use anyhttp::{
+    body::StreamBody, compression::Encoding, DynChunksStream, HttpError, HttpExecutor, PoolConfig,
+};
+use futures_util::{
+    future::{BoxFuture, Either},
+    AsyncReadExt, StreamExt, TryStreamExt,
+};
+use http::HeaderValue;
+
+/// Executor for [surf](https://docs.rs/surf), for async-std users.
+///
+/// Limitations:
+/// * sync streaming request bodies ([`anyhttp::RequestBody::Read`]) are not
+///   supported, async ones ([`anyhttp::RequestBody::Stream`]) are
+/// * [`anyhttp::RequestPre::connect_to`] is not supported
+/// * [`anyhttp::RequestPre::connect_timeout`] is ignored, surf has no
+///   per-request connect timeout
+/// * non-standard status codes (like `599`) can't be represented and fail
+///   the request
+/// * `Expect` request headers are not sent, since async-h1 can't handle
+///   interim responses
+/// * with the h1 client and keep-alive enabled, a connection may be reused
+///   while an abandoned response body is still unread, which breaks the next
+///   request. Disable keep-alive with `surf::Config::set_http_keep_alive`
+///   if responses are not always read to the end.
+#[derive(Clone)]
+pub struct SurfExecutor {
+    client: surf::Client,
+}
+
+impl SurfExecutor {
+    pub fn new(client: surf::Client) -> Self {
+        Self { client }
+    }
+
+    pub fn into_client(self) -> anyhttp::Client<Self> {
+        anyhttp::Client::new(self)
+    }
+}
+
+impl SurfExecutor {
+    /// Create an executor with the given pool settings.
+    ///
+    /// The h1 client of surf limits all connections per host, idle or not,
+    /// which is set from [`PoolConfig::max_idle_per_host`].
+    /// [`PoolConfig::max_idle`] and [`PoolConfig::idle_timeout`] are not
+    /// supported and ignored.
+    pub fn with_pool_config(config: PoolConfig) -> Result<Self, HttpError> {
+        let mut surf_config = surf::Config::new();
+        if let Some(max) = config.max_idle_per_host {
+            surf_config = surf_config.set_max_connections_per_host(max);
+        }
+        let client = surf::Client::try_from(surf_config)
+            .map_err(|err| HttpError::new_custom_with_cause("could not create surf client", err))?;
+        Ok(Self::new(client))
+    }
+}
+
+impl From<surf::Client> for SurfExecutor {
+    fn from(client: surf::Client) -> Self {
+        Self::new(client)
+    }
+}
+
+pub type ResponseBody = StreamBody;
+
+/// Wrapper that makes a `surf::Error` usable as an error cause.
+#[derive(Debug)]
+pub struct SurfError(pub surf::Error);
+
+impl std::fmt::Display for SurfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for SurfError {}
+
+fn map_error(err: surf::Error) -> HttpError {
+    let kind = err.downcast_ref::<std::io::Error>().map(|e| e.kind());
+    match kind {
+        Some(
+            std::io::ErrorKind::ConnectionRefused
+            | std::io::ErrorKind::ConnectionReset
+            | std::io::ErrorKind::AddrNotAvailable,
+        ) => HttpError::new_connect(SurfError(err)),
+        Some(std::io::ErrorKind::TimedOut) => HttpError::new_timeout(SurfError(err)),
+        // FIXME: more detailed mapping
+        _ => HttpError::new_custom_with_cause("surf error", SurfError(err)),
+    }
+}
+
+fn build_request(req: anyhttp::Request<anyhttp::RequestBody>) -> Result<surf::Request, HttpError> {
+    let method = surf::http::Method::from_str(req.method.as_str()).map_err(|_err| {
+        HttpError::new_custom(format!("method '{}' not supported by surf", req.method))
+    })?;
+    let url = surf::Url::parse(&req.uri.to_string())
+        .map_err(|err| HttpError::new_invalid_request(err, None))?;
+
+    let mut sr = surf::Request::new(method, url);
+    for (key, value) in &req.headers {
+        // async-h1 would return the interim `100 Continue` response as the
+        // final one.
+        if key == http::header::EXPECT {
+            continue;
+        }
+        let value = value.to_str().map_err(|_err| {
+            HttpError::new_custom(format!(
+                "could not re-parse request header '{key}': non-utf8 value"
+            ))
+        })?;
+        sr.append_header(key.as_str(), value);
+    }
+
+    match req.body {
+        anyhttp::RequestBody::Empty => {}
+        anyhttp::RequestBody::Bytes(bytes) => sr.set_body(surf::Body::from_bytes(bytes)),
+        anyhttp::RequestBody::Read(_) => {
+            // surf requires `Send` bodies.
+            return Err(HttpError::new_custom(
+                "streaming request bodies are not supported by the surf executor",
+            ));
+        }
+        anyhttp::RequestBody::Stream(stream) => {
+            let reader = SyncStream(std::sync::Mutex::new(stream.into_stream()))
+                .map_err(std::io::Error::other)
+                .into_async_read();
+            sr.set_body(surf::Body::from_reader(reader, None));
+        }
+    }
+    Ok(sr)
+}
+
+/// Makes a stream `Sync`, as required by surf bodies.
+///
+/// The stream is only accessed through `&mut`, so the lock is never taken.
+struct SyncStream(std::sync::Mutex<DynChunksStream>);
+
+impl futures_util::Stream for SyncStream {
+    type Item = Result<Vec<u8>, HttpError>;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        let stream = self
+            .get_mut()
+            .0
+            .get_mut()
+            .unwrap_or_else(|err| err.into_inner());
+        stream.poll_next_unpin(cx)
+    }
+}
+
+/// Read a surf body as a stream of chunks.
+///
+/// async-h1 treats a connection closed before `Content-Length` bytes were
+/// received as the end of the body, so the length is checked here.
+fn body_to_chunks(body: surf::Body, content_length: Option<u64>) -> DynChunksStream {
+    futures_util::stream::try_unfold((body, 0u64), move |(mut body, read)| async move {
+        let mut buffer = vec![0; 8 * 1024];
+        let count = body
+            .read(&mut buffer)
+            .await
+            .map_err(|err| HttpError::new_response_read(None, err))?;
+        if count == 0 {
+            if content_length.is_some_and(|len| read < len) {
+                let err = std::io::Error::from(std::io::ErrorKind::UnexpectedEof);
+                return Err(HttpError::new_response_read(
+                    Some("connection closed before the end of the body".to_string()),
+                    err,
+                ));
+            }
+            return Ok(None);
+        }
+        buffer.truncate(count);
+        Ok(Some((buffer, (body, read + count as u64))))
+    })
+    .boxed()
+}
+
+/// Fail a body stream with a timeout error once `deadline` has passed.
+fn chunks_with_deadline(chunks: DynChunksStream, deadline: std::time::Instant) -> DynChunksStream {
+    futures_util::stream::unfold(Some(chunks), move |chunks| async move {
+        // The stream ends after the timeout error.
+        let mut chunks = chunks?;
+        let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+        match async_std::future::timeout(remaining, chunks.next()).await {
+            Ok(item) => item.map(|item| (item, Some(chunks))),
+            Err(err) => Some((Err(HttpError::new_timeout(err)), None)),
+        }
+    })
+    .boxed()
+}
+
+impl HttpExecutor for SurfExecutor {
+    type RequestBody = anyhttp::RequestBody;
+    type ResponseBody = ResponseBody;
+    type Output = BoxFuture<'static, Result<anyhttp::Response<ResponseBody>, HttpError>>;
+
+    fn request_body_from_generic(&self, body: anyhttp::RequestBody) -> Self::RequestBody {
+        body
+    }
+
+    fn new_output_error(&self, error: HttpError) -> Self::Output {
+        Box::pin(std::future::ready(Err(error)))
+    }
+
+    fn generic_request_body_mut<'a>(
+        &self,
+        body: &'a mut Self::RequestBody,
+    ) -> Option<&'a mut anyhttp::RequestBody> {
+        Some(body)
+    }
+
+    #[cfg(feature = "tracing")]
+    fn instrument_output(
+        &self,
+        output: Self::Output,
+        span: anyhttp::trace::RequestSpan,
+    ) -> Self::Output {
+        Box::pin(span.instrument(output))
+    }
+
+    fn execute(&self, pre: anyhttp::RequestPre<Self::RequestBody>) -> Self::Output {
+        if pre.connect_to.is_some() {
+            return self.new_output_error(HttpError::new_custom(
+                "connect_to is not supported by the surf executor",
+            ));
+        }
+        let uri = pre.request.uri.clone();
+        let is_head = pre.request.method == http::Method::HEAD;
+        let decompress = pre.decompress;
+        let timeout = match pre.remaining_timeout() {
+            Ok(timeout) => timeout,
+            Err(err) => return self.new_output_error(err),
+        };
+        let deadline = timeout.map(|timeout| std::time::Instant::now() + timeout);
+        let tap = pre.tap;
+        let cancel = pre.cancel;
+        // async-h1 ends the body early instead of failing when a stream
+        // errors, so limited streams are buffered to enforce the limit.
+        // The limit also bounds the buffer.
+        let mut request = pre.request;
+        let (body, limited_stream) = match request.body {
+            anyhttp::RequestBody::Stream(stream) if pre.max_request_body_size.is_some() => {
+                (anyhttp::RequestBody::Empty, Some(stream))
+            }
+            body => (body, None),
+        };
+        request.body = body;
+        let mut sr = match build_request(request) {
+            Ok(sr) => sr,
+            Err(err) => return self.new_output_error(err),
+        };
+        let client = self.client.clone();
+
+        Box::pin(async move {
+            if let Some(stream) = limited_stream {
+                let body = stream.into_stream().try_concat().await?;
+                sr.set_body(surf::Body::from_bytes(body));
+            }
+            let send = client.send(sr);
+            let send = async {
+                match timeout {
+                    Some(timeout) => async_std::future::timeout(timeout, send)
+                        .await
+                        .map_err(HttpError::new_timeout)?,
+                    None => send.await,
+                }
+                .map_err(map_error)
+            };
+            let mut sres = match cancel {
+                Some(cancel) => {
+                    let cancelled = cancel.cancelled();
+                    futures_util::pin_mut!(send, cancelled);
+                    match futures_util::future::select(cancelled, send).await {
+                        Either::Left(_) => return Err(HttpError::new_cancelled()),
+                        Either::Right((res, _)) => res?,
+                    }
+                }
+                None => send.await?,
+            };
+
+            let status = http::StatusCode::from_u16(sres.status().into())
+                .map_err(|err| HttpError::new_http(err.into()))?;
+
+            let mut headers = http::HeaderMap::new();
+            for (name, values) in sres.iter() {
+                let key = http::header::HeaderName::from_str(name.as_str())
+                    .map_err(|err| HttpError::new_http(err.into()))?;
+                for value in values {
+                    // async-h1 adds a malformed `date` header if the server
+                    // didn't send one, so invalid values are skipped.
+                    if let Ok(value) = HeaderValue::from_str(value.as_str()) {
+                        headers.append(key.clone(), value);
+                    }
+                }
+            }
+
+            // async-h1 doesn't know the request method and would wait for a
+            // HEAD response body that never comes.
+            let mut chunks = if is_head {
+                futures_util::stream::empty().boxed()
+            } else {
+                let content_length = headers
+                    .get(http::header::CONTENT_LENGTH)
+                    .and_then(|v| v.to_str().ok()?.parse().ok());
+                body_to_chunks(sres.take_body(), content_length)
+            };
+            if decompress && !is_head {
+                if let Some(encoding) = Encoding::take_from_headers(&mut headers) {
+                    chunks = encoding.decode_stream(chunks);
+                }
+            }
+            if let Some(deadline) = deadline {
+                chunks = chunks_with_deadline(chunks, deadline);
+            }
+
+            let mut res = anyhttp::Response {
+                uri: Some(uri),
+                status,
+                version: http::Version::HTTP_11,
+                headers,
+                extensions: Default::default(),
+                body: (),
+            };
+            if let Some(tap) = tap {
+                anyhttp::run_tap(&tap, &mut res)?;
+            }
+
+            Ok(res.map_body(|_| StreamBody::from(chunks)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[async_std::test]
+    async fn test_surf_client() {
+        let client: surf::Client = surf::Config::new()
+            .set_http_keep_alive(false)
+            .try_into()
+            .unwrap();
+        let exec = SurfExecutor::new(client);
+        // `test_async_executor_strict` is skipped: http-types can't
+        // represent non-standard status codes and doesn't keep the header
+        // order, and concurrent requests occasionally stall until the
+        // timeout.
+        anyhttp::test::test_async_executor(exec).await;
+    }
+}
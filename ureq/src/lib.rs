@@ -1,18 +1,175 @@
-use std::str::FromStr;
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
-use anyhttp::{sync::GenericResponseBody, HttpError, HttpExecutor};
+use anyhttp::{
+    sync::{read_final_response, GenericResponseBody},
+    HttpError, HttpExecutor, PoolConfig, TlsConfig,
+};
 use http::HeaderValue;
 
+/// Executor for [ureq](https://docs.rs/ureq).
+///
+/// `Expect` request headers are sent, but the body follows without waiting
+/// for the server. ureq returns interim responses as the final one, so the
+/// executor reads the actual response itself; such connections are not
+/// reused.
+/// Async [`anyhttp::RequestBody::Stream`] bodies are buffered before they
+/// are sent.
+/// ureq only supports connect timeouts per agent, so requests with
+/// [`anyhttp::RequestPre::connect_timeout`] use an agent for that timeout,
+/// with its own connection pool.
 #[derive(Clone)]
 pub struct UreqExecutor {
     agent: ureq::Agent,
+    settings: Arc<AgentSettings>,
+    /// Agents for requests with a connect timeout, which ureq only supports
+    /// per agent. Each has its own connection pool.
+    connect_timeout_agents: Arc<Mutex<HashMap<Duration, ureq::Agent>>>,
+}
+
+/// The settings of the agents of an executor.
+#[derive(Default)]
+struct AgentSettings {
+    pool: PoolConfig,
+    tls: Option<Arc<rustls::ClientConfig>>,
+}
+
+impl AgentSettings {
+    fn builder(&self) -> ureq::AgentBuilder {
+        let mut builder = ureq::AgentBuilder::new();
+        if let Some(max) = self.pool.max_idle_per_host {
+            builder = builder.max_idle_connections_per_host(max);
+        }
+        if let Some(max) = self.pool.max_idle {
+            builder = builder.max_idle_connections(max);
+        }
+        if let Some(tls) = &self.tls {
+            builder = builder.tls_config(tls.clone());
+        }
+        builder
+    }
 }
 
 impl UreqExecutor {
     pub fn new() -> Self {
+        Self::from_settings(AgentSettings::default())
+    }
+
+    fn from_settings(settings: AgentSettings) -> Self {
         Self {
-            agent: ureq::agent(),
+            agent: settings.builder().build(),
+            settings: Arc::new(settings),
+            connect_timeout_agents: Default::default(),
+        }
+    }
+
+    /// The agent for a request with the given connect timeout.
+    fn agent(&self, connect_timeout: Option<Duration>) -> ureq::Agent {
+        let Some(timeout) = connect_timeout else {
+            return self.agent.clone();
+        };
+        let mut agents = self.connect_timeout_agents.lock().unwrap();
+        agents
+            .entry(timeout)
+            .or_insert_with(|| self.settings.builder().timeout_connect(timeout).build())
+            .clone()
+    }
+
+    /// Create an executor with the given pool settings.
+    ///
+    /// ureq doesn't close idle connections after a timeout, so
+    /// [`PoolConfig::idle_timeout`] is ignored.
+    pub fn with_pool_config(config: PoolConfig) -> Self {
+        Self::from_settings(AgentSettings {
+            pool: config,
+            tls: None,
+        })
+    }
+
+    /// Create an executor with the given TLS settings.
+    ///
+    /// ureq uses rustls. The custom root certificates are trusted in
+    /// addition to the bundled webpki roots.
+    pub fn with_tls_config(config: &TlsConfig) -> Result<Self, HttpError> {
+        let tls = rustls_config(config)?;
+        Ok(Self::from_settings(AgentSettings {
+            pool: PoolConfig::default(),
+            tls: Some(Arc::new(tls)),
+        }))
+    }
+}
+
+fn rustls_config(config: &TlsConfig) -> Result<rustls::ClientConfig, HttpError> {
+    let mut roots = rustls::RootCertStore::empty();
+    roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|ta| {
+        rustls::OwnedTrustAnchor::from_subject_spki_name_constraints(
+            ta.subject,
+            ta.spki,
+            ta.name_constraints,
+        )
+    }));
+    for cert in &config.root_certs {
+        roots
+            .add(&rustls::Certificate(cert.der().to_vec()))
+            .map_err(|err| HttpError::new_custom_with_cause("invalid root certificate", err))?;
+    }
+
+    let builder = rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots);
+    let mut tls = match &config.identity {
+        Some(identity) => {
+            let certs = identity
+                .certs()
+                .iter()
+                .map(|cert| rustls::Certificate(cert.der().to_vec()))
+                .collect();
+            let key = rustls::PrivateKey(identity.key_der().to_vec());
+            builder
+                .with_client_auth_cert(certs, key)
+                .map_err(|err| HttpError::new_custom_with_cause("invalid client identity", err))?
         }
+        None => builder.with_no_client_auth(),
+    };
+    tls.alpn_protocols = config
+        .alpn_protocols
+        .iter()
+        .map(|p| p.as_bytes().to_vec())
+        .collect();
+    #[cfg(feature = "dangerous")]
+    if config.accepts_invalid_certs() {
+        tls.dangerous()
+            .set_certificate_verifier(Arc::new(AcceptAnyCert));
+    }
+    Ok(tls)
+}
+
+/// Certificate verifier for [`TlsConfig::accepts_invalid_certs`].
+#[cfg(feature = "dangerous")]
+struct AcceptAnyCert;
+
+#[cfg(feature = "dangerous")]
+impl rustls::client::ServerCertVerifier for AcceptAnyCert {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &rustls::Certificate,
+        _intermediates: &[rustls::Certificate],
+        _server_name: &rustls::ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: std::time::SystemTime,
+    ) -> Result<rustls::client::ServerCertVerified, rustls::Error> {
+        Ok(rustls::client::ServerCertVerified::assertion())
+    }
+}
+
+impl Default for UreqExecutor {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -30,13 +187,46 @@ impl HttpExecutor for UreqExecutor {
         Err(error)
     }
 
+    fn generic_request_body_mut<'a>(
+        &self,
+        body: &'a mut Self::RequestBody,
+    ) -> Option<&'a mut anyhttp::RequestBody> {
+        Some(body)
+    }
+
+    #[cfg(feature = "tracing")]
+    fn instrument_output(
+        &self,
+        output: Self::Output,
+        span: anyhttp::trace::RequestSpan,
+    ) -> Self::Output {
+        span.finish(&output);
+        output
+    }
+
     fn execute(&self, pre: anyhttp::RequestPre<Self::RequestBody>) -> Self::Output {
+        // ureq requests are blocking, so the token can only be checked
+        // before sending.
+        if pre.cancel.as_ref().is_some_and(|c| c.is_cancelled()) {
+            return Err(HttpError::new_cancelled());
+        }
+        if pre.connect_to.is_some() {
+            return Err(HttpError::new_custom(
+                "connect_to is not supported by the ureq executor",
+            ));
+        }
+        let timeout = pre.remaining_timeout()?;
         let req = pre.request;
         let tap = pre.tap;
+        let decompress = pre.decompress;
+        let is_head = req.method == http::Method::HEAD;
 
         let mut ur = self
-            .agent
+            .agent(pre.connect_timeout)
             .request(req.method.as_str(), &req.uri.to_string());
+        if let Some(timeout) = timeout {
+            ur = ur.timeout(timeout);
+        }
 
         for key in req.headers.keys() {
             for value in req.headers.get_all(key) {
@@ -49,61 +239,118 @@ impl HttpExecutor for UreqExecutor {
             }
         }
 
+        // `call` sends no `Content-Length`, but servers may reject mutating
+        // requests without one.
+        let needs_length = matches!(
+            req.method,
+            http::Method::POST | http::Method::PUT | http::Method::PATCH
+        );
         let result = match req.body {
+            anyhttp::RequestBody::Empty if needs_length => ur.send_bytes(&[]),
             anyhttp::RequestBody::Empty => ur.call(),
             anyhttp::RequestBody::Bytes(bytes) => ur.send_bytes(&bytes),
             anyhttp::RequestBody::Read(r) => ur.send(r),
+            anyhttp::RequestBody::Stream(stream) => ur.send_bytes(&stream.read_to_end_blocking()?),
         };
 
         let ures = match result {
             Ok(r) => r,
             Err(ureq::Error::Status(_status, res)) => res,
-            Err(err) => {
-                // FIXME: better mapping
-                return Err(HttpError::new_custom(err.to_string()));
-            }
+            Err(ureq::Error::Transport(err)) => return Err(map_transport_error(err)),
         };
 
+        // The final URI is informational, so a URI that `http` can't parse,
+        // like an overlong redirect target, doesn't fail the request.
         let uri = ures
             .get_url()
             .parse::<http::Uri>()
-            .map_err(|err| HttpError::new_http(err.into()))?;
+            .unwrap_or_else(|_| req.uri.clone());
 
         let status = http::StatusCode::from_u16(ures.status())
             .map_err(|err| HttpError::new_http(err.into()))?;
+        if status.is_informational() && status != http::StatusCode::SWITCHING_PROTOCOLS {
+            // ureq returns interim responses, like `100 Continue` for an
+            // `Expect` header, as the final one, with the rest of the
+            // connection as the body.
+            let res = read_final_response(Box::new(ures.into_reader()), is_head)?;
+            return finish_response(res, uri, decompress, is_head, tap);
+        }
 
         let mut headers = http::HeaderMap::new();
         for header in ures.headers_names() {
-            if let Some(value_raw) = ures.header(&header) {
-                let key = http::header::HeaderName::from_str(&header)
-                    .map_err(|err| HttpError::new_http(err.into()))?;
-
+            let key = http::header::HeaderName::from_str(&header)
+                .map_err(|err| HttpError::new_http(err.into()))?;
+            // Names are listed once per header line, but `all` already
+            // returns every value.
+            if headers.contains_key(&key) {
+                continue;
+            }
+            for value_raw in ures.all(&header) {
                 let value = value_raw
                     .parse::<HeaderValue>()
                     .map_err(|err| HttpError::new_http(err.into()))?;
-                headers.append(key, value);
+                headers.append(&key, value);
             }
         }
 
-        let body = GenericResponseBody::Read(Box::new(ures.into_reader()));
+        let mut extensions = http::Extensions::new();
+        extensions.insert(anyhttp::ReasonPhrase(ures.status_text().to_string()));
 
-        let mut res = anyhttp::Response {
-            uri: Some(uri),
+        let res = anyhttp::Response {
+            uri: None,
             status,
             version: http::Version::HTTP_11,
             headers,
-            extensions: Default::default(),
-            body: (),
+            extensions,
+            body: Box::new(ures.into_reader()) as Box<dyn std::io::Read>,
         };
-        if let Some(tap) = tap {
-            tap(&mut res)
-        }
+        finish_response(res, uri, decompress, is_head, tap)
+    }
+}
+
+fn finish_response(
+    res: anyhttp::Response<Box<dyn std::io::Read>>,
+    uri: http::Uri,
+    decompress: bool,
+    is_head: bool,
+    tap: Option<anyhttp::Tapper>,
+) -> Result<anyhttp::Response<GenericResponseBody>, HttpError> {
+    let (mut res, reader) = res.take_body();
+    res.uri = Some(uri);
+    // ureq returns an empty body for HEAD responses, which can't be
+    // decoded.
+    let reader = if decompress && !is_head {
+        anyhttp::compression::decode_reader(&mut res.headers, reader)
+    } else {
+        reader
+    };
+    if let Some(tap) = tap {
+        anyhttp::run_tap(&tap, &mut res)?;
+    }
+    Ok(res.map_body(|_| GenericResponseBody::Read(reader)))
+}
 
-        let final_res = res.map_body(|_| body);
-        Ok(final_res)
+fn map_transport_error(err: ureq::Transport) -> HttpError {
+    match err.kind() {
+        ureq::ErrorKind::Dns
+        | ureq::ErrorKind::ConnectionFailed
+        | ureq::ErrorKind::ProxyConnect => HttpError::new_connect(err),
+        ureq::ErrorKind::Io if is_timeout(&err) => HttpError::new_timeout(err),
+        _ => HttpError::new_custom_with_cause("ureq transport error", err),
     }
 }
 
+fn is_timeout(err: &ureq::Transport) -> bool {
+    std::error::Error::source(err)
+        .and_then(|e| e.downcast_ref::<std::io::Error>())
+        .is_some_and(|e| {
+            matches!(
+                e.kind(),
+                std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock
+            )
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use anyhttp::Client;
@@ -117,6 +364,36 @@ mod tests {
         // Ensure client works as dynamic.
         let _dyn = Client::new_dyn_sync(exec.clone());
 
-        anyhttp::test::test_sync_executor(exec);
+        anyhttp::test::test_sync_executor(exec.clone());
+        // The harness sends a request with a connect timeout.
+        assert_eq!(exec.connect_timeout_agents.lock().unwrap().len(), 1);
+
+        let exec = UreqExecutor::with_pool_config(PoolConfig::new().max_idle_per_host(2));
+        anyhttp::test::test_sync_executor_strict(exec);
+
+        assert!(anyhttp::Certificate::from_pem(b"").is_err());
+        let tls = TlsConfig::new().alpn_protocols(["http/1.1"]);
+        UreqExecutor::with_tls_config(&tls).unwrap();
+    }
+
+    #[test]
+    fn test_ureq_transport_error_cause() {
+        use std::io::Write;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.write_all(b"garbage\r\n\r\n").unwrap();
+        });
+
+        let client = Client::new(UreqExecutor::new());
+        let Err(err) = client.get(format!("http://{addr}/")).send() else {
+            panic!("expected a transport error");
+        };
+        server.join().unwrap();
+
+        let transport = err.downcast_ref::<ureq::Transport>().unwrap();
+        assert_eq!(transport.kind(), ureq::ErrorKind::BadStatus);
     }
 }